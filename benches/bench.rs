@@ -91,3 +91,45 @@ fn decoded_records(b: &mut Bencher) {
         for r in dec.decode::<Play>() { let _ = r.unwrap(); }
     })
 }
+
+#[bench]
+fn string_records_arena(b: &mut Bencher) {
+    let mut data = file_to_mem(CSV_DATA);
+    b.bytes = data.get_ref().len() as u64;
+    b.iter(|| {
+        let mut dec = reader(&mut data);
+        let mut batches = dec.records_arena(1024);
+        while let Some(batch) = batches.next_batch() {
+            for r in batch.unwrap().iter() { let _ = r; }
+        }
+    })
+}
+
+fn single_column_data() -> io::Cursor<Vec<u8>> {
+    let mut data = String::from("id\n");
+    for i in 0..100000 {
+        data.push_str(&format!("item{}\n", i));
+    }
+    io::Cursor::new(data.into_bytes())
+}
+
+#[bench]
+fn single_column_records(b: &mut Bencher) {
+    let mut data = single_column_data();
+    b.bytes = data.get_ref().len() as u64;
+    b.iter(|| {
+        let mut dec = reader(&mut data);
+        for r in dec.records() { let _ = r.unwrap(); }
+    })
+}
+
+#[bench]
+fn single_column_fast(b: &mut Bencher) {
+    let mut data = single_column_data();
+    b.bytes = data.get_ref().len() as u64;
+    b.iter(|| {
+        let mut dec = reader(&mut data);
+        let mut buf = String::new();
+        while ordie(dec.read_single_column(&mut buf)) {}
+    })
+}
@@ -2,6 +2,7 @@ use std::default::Default;
 use std::str::FromStr;
 
 use rustc_serialize as serialize;
+use rustc_serialize::Decodable;
 
 use {ByteString, Result, Error};
 
@@ -15,13 +16,52 @@ use {ByteString, Result, Error};
 pub struct Decoded {
     stack: Vec<ByteString>,
     popped: usize,
+    null: Option<ByteString>,
+    record_len: usize,
+    // How many tuples and structs are being decoded into.
+    depth: usize,
+    // The number of elements of the tuple that the whole record was decoded
+    // into, if any.
+    tuple_len: Option<usize>,
 }
 
 impl Decoded {
     /// Creates a new decodable record from a record of byte strings.
     pub fn new(mut bytes: Vec<ByteString>) -> Decoded {
         bytes.reverse();
-        Decoded { stack: bytes, popped: 0 }
+        Decoded {
+            record_len: bytes.len(),
+            stack: bytes,
+            popped: 0,
+            null: None,
+            depth: 0,
+            tuple_len: None,
+        }
+    }
+
+    /// Sets a field value that stands for a missing value.
+    ///
+    /// A field equal to `marker` is decoded as `None` into an `Option`
+    /// field, and results in a decoding error for any other type.
+    pub fn null_value<S: Into<ByteString>>(mut self, marker: S) -> Decoded {
+        self.null = Some(marker.into());
+        self
+    }
+
+    /// Decodes this record into a value of type `D`.
+    ///
+    /// This is just like `Decodable::decode`, except that a record decoded
+    /// as a whole into a tuple must have exactly as many fields as the tuple
+    /// has elements. (Missing fields are always an error, but extra fields
+    /// would otherwise be ignored.)
+    pub fn decode<D: Decodable>(&mut self) -> Result<D> {
+        let v = try!(D::decode(self));
+        match self.tuple_len {
+            Some(len) if self.len() > 0 => self.err(format!(
+                "Tuple has {} fields but current record has {} fields.",
+                len, self.record_len)),
+            _ => Ok(v),
+        }
     }
 
     fn len(&self) -> usize {
@@ -31,6 +71,11 @@ impl Decoded {
 
 impl Decoded {
     fn pop(&mut self) -> Result<ByteString> {
+        if self.next_is_null() {
+            return self.err(format!(
+                "Found null value '{}' in a field that is not optional.",
+                String::from_utf8_lossy(&self.stack[self.stack.len() - 1])));
+        }
         self.popped += 1;
         match self.stack.pop() {
             None => self.err(format!(
@@ -41,6 +86,10 @@ impl Decoded {
         }
     }
 
+    fn next_is_null(&self) -> bool {
+        self.null.is_some() && self.stack.last() == self.null.as_ref()
+    }
+
     fn pop_string(&mut self) -> Result<String> {
         String::from_utf8(try!(self.pop())).map_err(|bytes| {
             Error::Decode(
@@ -58,6 +107,7 @@ impl Decoded {
     }
 
     fn push(&mut self, s: ByteString) {
+        self.popped -= 1;
         self.stack.push(s);
     }
 
@@ -143,16 +193,25 @@ impl serialize::Decoder for Decoded {
                 format!("Struct '{}' has {} fields but current record \
                          has {} fields.", s_name, len, self.len()));
         }
-        f(self)
+        self.depth += 1;
+        let v = f(self);
+        self.depth -= 1;
+        v
     }
     fn read_struct_field<T, F>(&mut self, _: &str, _: usize, f: F)
                               -> Result<T>
             where F: FnOnce(&mut Decoded) -> Result<T> {
         f(self)
     }
-    fn read_tuple<T, F>(&mut self, _: usize, f: F) -> Result<T>
+    fn read_tuple<T, F>(&mut self, len: usize, f: F) -> Result<T>
             where F: FnOnce(&mut Decoded) -> Result<T> {
-        f(self)
+        if self.depth == 0 {
+            self.tuple_len = Some(len);
+        }
+        self.depth += 1;
+        let v = f(self);
+        self.depth -= 1;
+        v
     }
     fn read_tuple_arg<T, F>(&mut self, _: usize, f: F) -> Result<T>
             where F: FnOnce(&mut Decoded) -> Result<T> {
@@ -170,14 +229,26 @@ impl serialize::Decoder for Decoded {
     }
     fn read_option<T, F>(&mut self, mut f: F) -> Result<T>
             where F: FnMut(&mut Decoded, bool) -> Result<T> {
+        if self.next_is_null() {
+            self.stack.pop();
+            self.popped += 1;
+            return f(self, false);
+        }
         let s = try!(self.pop_string());
         if s.is_empty() {
             f(self, false)
         } else {
             self.push_string(s);
+            let (len, popped) = (self.len(), self.popped);
             match f(self, true) {
                 Ok(v) => Ok(v),
-                Err(_) => f(self, false),
+                Err(_) => {
+                    // The field couldn't be decoded, so it is consumed as
+                    // `None` instead.
+                    self.stack.truncate(len - 1);
+                    self.popped = popped + (len - self.len());
+                    f(self, false)
+                }
             }
         }
     }
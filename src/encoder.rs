@@ -2,6 +2,25 @@ use rustc_serialize as serialize;
 
 use {ByteString, Result, Error};
 
+/// The format to use when encoding floating point numbers.
+#[derive(Clone, Copy, Debug)]
+pub enum FloatFormat {
+    /// Up to 10 decimal places, with trailing zeros removed (but always at
+    /// least one decimal place). e.g., `1.5` is written as `1.5` and `1.0`
+    /// as `1.0`.
+    ///
+    /// This is the default.
+    Trimmed,
+    /// Exactly the given number of decimal places. e.g., with `Fixed(2)`,
+    /// `1.5` is written as `1.50` and `1.005` as `1.00` or `1.01`
+    /// (depending on its binary representation).
+    Fixed(usize),
+    /// The shortest representation that reads back as the same number
+    /// (i.e., Rust's `Display`). e.g., `1.5` is written as `1.5` and `1.0`
+    /// as `1`.
+    Shortest,
+}
+
 /// A record to be encoded.
 ///
 /// This is a "wrapper" type that allows the `Encoder` machinery from the
@@ -11,12 +30,36 @@ use {ByteString, Result, Error};
 /// should prefer the `encode` or `encode_all` methods defined on `CsvWriter`.
 pub struct Encoded {
     record: Vec<ByteString>,
+    float_format: FloatFormat,
+    null: Option<ByteString>,
 }
 
 impl Encoded {
     /// Creates a new encodable record. The value returned can be passed to
     /// `Encodable::encode`.
-    pub fn new() -> Encoded { Encoded { record: vec![] } }
+    pub fn new() -> Encoded {
+        Encoded {
+            record: vec![],
+            float_format: FloatFormat::Trimmed,
+            null: None,
+        }
+    }
+
+    /// Sets the format to use for floating point numbers.
+    ///
+    /// Integers, strings and other values are not affected.
+    pub fn float_format(mut self, format: FloatFormat) -> Encoded {
+        self.float_format = format;
+        self
+    }
+
+    /// Sets the field value to write for a `None` value.
+    ///
+    /// By default, `None` is written as an empty field.
+    pub fn null_value<S: Into<ByteString>>(mut self, marker: S) -> Encoded {
+        self.null = Some(marker.into());
+        self
+    }
 
     /// Once a record has been encoded into this value, `unwrap` can be used
     /// to access the raw CSV record.
@@ -36,6 +79,15 @@ impl Encoded {
     fn push_to_string<T: ToString>(&mut self, t: T) -> Result<()> {
         self.push_string(t.to_string())
     }
+
+    fn push_float(&mut self, v: f64) -> Result<()> {
+        let s = match self.float_format {
+            FloatFormat::Trimmed => float_to_string(v),
+            FloatFormat::Fixed(places) => format!("{:.*}", places, v),
+            FloatFormat::Shortest => v.to_string(),
+        };
+        self.push_string(s)
+    }
 }
 
 impl serialize::Encoder for Encoded {
@@ -60,10 +112,14 @@ impl serialize::Encoder for Encoded {
         self.push_to_string(v)
     }
     fn emit_f64(&mut self, v: f64) -> Result<()> {
-        self.push_string(float_to_string(v))
+        self.push_float(v)
     }
     fn emit_f32(&mut self, v: f32) -> Result<()> {
-        self.push_string(float_to_string(v as f64))
+        match self.float_format {
+            // Widening to `f64` first would expose the error in `v`.
+            FloatFormat::Shortest => self.push_string(v.to_string()),
+            _ => self.push_float(v as f64),
+        }
     }
     fn emit_char(&mut self, v: char) -> Result<()> {
         self.push_string(format!("{}", v))
@@ -133,7 +189,8 @@ impl serialize::Encoder for Encoded {
         f(self)
     }
     fn emit_option_none(&mut self) -> Result<()> {
-        self.push_bytes::<&[u8]>(&[])
+        let null = self.null.clone().unwrap_or(vec![]);
+        self.push_bytes(null)
     }
     fn emit_option_some<F>(&mut self, f: F) -> Result<()>
                        where F: FnOnce(&mut Encoded) -> Result<()> {
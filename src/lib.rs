@@ -192,12 +192,19 @@ use std::io;
 use std::result;
 
 pub use borrow_bytes::BorrowBytes;
-pub use encoder::Encoded;
+pub use encoder::{Encoded, FloatFormat};
 pub use decoder::Decoded;
+pub use merge::MergeReader;
 pub use reader::{
-    Reader, DecodedRecords, StringRecords, ByteRecords, NextField,
-    RecordTerminator,
+    Reader, EscapeMode, OnError, DecodedRecords, StringRecords, ByteRecords,
+    NextField, RecordTerminator, ArenaRecords, RecordBatch, RecordBatchIter,
+    ArenaRecord, ArenaFields, DedupRecords, StringRecordsIntoIter,
+    ReadInto, LookaheadReader, TakeRecordsWhile, DedupRecordsBy,
+    LazyRecords, LazyStringRecord, RecordsWithHeaders, Windows2, GroupBy,
+    ReaderStats, looks_delimited, ColumnType, ColumnKind, ColumnSource,
+    ColumnFn, RecordArray,
 };
+pub use transcode::{Utf16Transcoder, ChunkReader};
 pub use writer::{Writer, QuoteStyle};
 
 macro_rules! lg {
@@ -212,7 +219,9 @@ pub mod index;
 mod borrow_bytes;
 mod encoder;
 mod decoder;
+mod merge;
 mod reader;
+mod transcode;
 mod writer;
 
 #[cfg(test)]
@@ -238,6 +247,8 @@ pub enum Error {
     Io(io::Error),
     /// An error originating from using a CSV index.
     Index(String),
+    /// An error in the configuration of a CSV reader or writer.
+    Config(String),
 }
 
 /// An error tagged with a location at which it occurred.
@@ -245,7 +256,7 @@ pub enum Error {
 pub struct LocatableError<T> {
     /// The record number (starting at 1).
     pub record: u64,
-    /// The field number (starting at 1).
+    /// The field number (starting at 0).
     pub field: u64,
     /// The error.
     pub err: T,
@@ -268,6 +279,53 @@ pub enum ParseError {
     ///
     /// TODO: Include the real Utf8Error, but it is not stabilized yet.
     InvalidUtf8,
+    /// A quoted field contained too many embedded line terminators.
+    ///
+    /// This is only reported when `max_field_lines` is set on the
+    /// corresponding CSV reader.
+    FieldTooManyLines {
+        /// The maximum number of line terminators allowed in a field.
+        max: u64,
+    },
+    /// A record contains too many fields.
+    ///
+    /// This is only reported when `max_fields` is set on the corresponding
+    /// CSV reader.
+    TooManyFields {
+        /// The maximum number of fields allowed in a record.
+        max: u64,
+    },
+    /// There is data left after the point where it was expected to end.
+    ///
+    /// This is only reported by `assert_eof` on a CSV reader.
+    TrailingData {
+        /// The byte offset at which the unexpected data starts.
+        pos: u64,
+    },
+    /// A record doesn't have a column selected for projection.
+    ///
+    /// This is only reported when `project_strict` is enabled on the
+    /// corresponding CSV reader.
+    MissingColumn {
+        /// The index (starting at `0`) of the missing column.
+        column: u64,
+    },
+    /// An escape in a quoted field isn't followed by a quote or an escape.
+    ///
+    /// This is only reported when `strict_escape` is enabled on the
+    /// corresponding CSV reader.
+    InvalidEscape {
+        /// The byte offset of the escape character.
+        pos: u64,
+    },
+    /// A quoted field has content after its closing quote.
+    ///
+    /// This is only reported when `error_trailing_quote_content` is enabled
+    /// on the corresponding CSV reader.
+    TrailingQuoteContent {
+        /// The byte offset of the first byte after the closing quote.
+        pos: u64,
+    },
 }
 
 impl fmt::Display for Error {
@@ -278,6 +336,7 @@ impl fmt::Display for Error {
             Error::Parse(ref err) => write!(f, "{}", err),
             Error::Io(ref err) => write!(f, "{}", err),
             Error::Index(ref msg) => write!(f, "CSV index error: {}", msg),
+            Error::Config(ref msg) => write!(f, "CSV config error: {}", msg),
         }
     }
 }
@@ -297,6 +356,20 @@ impl fmt::Display for ParseError {
                            with length {}.", expected, got),
             ParseError::InvalidUtf8 =>
                 write!(f, "Invalid UTF8 encoding."),
+            ParseError::FieldTooManyLines { max } =>
+                write!(f, "Quoted field contains more than {} line \
+                           terminators.", max),
+            ParseError::TooManyFields { max } =>
+                write!(f, "Record contains more than {} fields.", max),
+            ParseError::TrailingData { pos } =>
+                write!(f, "Unexpected data at byte {}.", pos),
+            ParseError::MissingColumn { column } =>
+                write!(f, "Record has no column at index {}.", column),
+            ParseError::InvalidEscape { pos } =>
+                write!(f, "Invalid escape sequence at byte {}.", pos),
+            ParseError::TrailingQuoteContent { pos } =>
+                write!(f, "Unexpected content after closing quote at \
+                           byte {}.", pos),
         }
     }
 }
@@ -309,6 +382,7 @@ impl StdError for Error {
             Error::Parse(..) => "CSV parse error",
             Error::Io(..) => "CSV IO error",
             Error::Index(..) => "CSV indexing error",
+            Error::Config(..) => "CSV configuration error",
         }
     }
 
@@ -0,0 +1,130 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::io;
+
+use {Error, Reader, Result};
+
+/// An iterator that merges the records of several sorted CSV readers.
+///
+/// Given a set of readers whose records are each individually sorted by
+/// some key, this yields the records of all readers in a single stream
+/// sorted by that same key. This is the classic k-way merge used to combine
+/// pre-sorted shards of a large data set.
+///
+/// Only one record per reader is kept in memory at any given time, and each
+/// reader is only advanced when its current record has been yielded.
+///
+/// Records with equal keys are yielded in the order of their readers, so
+/// the merge is stable with respect to the order of the input readers.
+///
+/// Each reader's `has_headers` setting is respected, so header rows are
+/// never part of the merged output.
+///
+/// If a reader returns an error, then that error is yielded and the reader
+/// is dropped from the merge. The remaining readers are still merged.
+///
+/// ### Example
+///
+/// ```rust
+/// let shard1 = csv::Reader::from_string("id,name\n1,a\n4,d\n");
+/// let shard2 = csv::Reader::from_string("id,name\n2,b\n3,c\n5,e\n");
+///
+/// let key = |r: &[String]| r[0].parse::<u32>().unwrap();
+/// let merged = csv::MergeReader::new(vec![shard1, shard2], key);
+/// let ids: Vec<String> = merged.map(|r| r.unwrap()[0].clone()).collect();
+/// assert_eq!(ids, vec!["1", "2", "3", "4", "5"]);
+/// ```
+pub struct MergeReader<R, K, F> {
+    rdrs: Vec<Option<Reader<R>>>,
+    key: F,
+    heap: BinaryHeap<Head<K>>,
+    refill: Vec<usize>,
+}
+
+impl<R, K, F> MergeReader<R, K, F>
+        where R: io::Read, K: Ord, F: FnMut(&[String]) -> K {
+    /// Creates a new merge over the readers given.
+    ///
+    /// Every reader must already be sorted by `key`. If one isn't, then the
+    /// merged output won't be sorted either. (This is not checked.)
+    pub fn new(rdrs: Vec<Reader<R>>, key: F) -> MergeReader<R, K, F> {
+        let refill = (0..rdrs.len()).collect();
+        MergeReader {
+            rdrs: rdrs.into_iter().map(Some).collect(),
+            key: key,
+            heap: BinaryHeap::new(),
+            refill: refill,
+        }
+    }
+
+    /// Reads the next record from each reader whose record was consumed.
+    fn fill(&mut self) -> Option<Error> {
+        while let Some(src) = self.refill.pop() {
+            let next = match self.rdrs[src] {
+                None => continue,
+                Some(ref mut rdr) => rdr.records().next(),
+            };
+            match next {
+                None => { self.rdrs[src] = None; }
+                Some(Err(err)) => {
+                    self.rdrs[src] = None;
+                    return Some(err);
+                }
+                Some(Ok(record)) => {
+                    let key = (self.key)(&record);
+                    self.heap.push(Head {
+                        key: key,
+                        src: src,
+                        record: record,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<R, K, F> Iterator for MergeReader<R, K, F>
+        where R: io::Read, K: Ord, F: FnMut(&[String]) -> K {
+    type Item = Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Result<Vec<String>>> {
+        if let Some(err) = self.fill() {
+            return Some(Err(err));
+        }
+        self.heap.pop().map(|head| {
+            self.refill.push(head.src);
+            Ok(head.record)
+        })
+    }
+}
+
+/// The current record of a single reader in a merge.
+///
+/// The ordering is reversed so that `BinaryHeap` (a max-heap) pops the
+/// smallest key first, with ties broken by the reader's position.
+struct Head<K> {
+    key: K,
+    src: usize,
+    record: Vec<String>,
+}
+
+impl<K: Ord> Ord for Head<K> {
+    fn cmp(&self, other: &Head<K>) -> Ordering {
+        (&other.key, other.src).cmp(&(&self.key, self.src))
+    }
+}
+
+impl<K: Ord> PartialOrd for Head<K> {
+    fn partial_cmp(&self, other: &Head<K>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord> PartialEq for Head<K> {
+    fn eq(&self, other: &Head<K>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord> Eq for Head<K> {}
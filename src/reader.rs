@@ -1,19 +1,31 @@
+use std::cell::Cell;
+use std::cmp;
+use std::collections::VecDeque;
 use std::fs;
 use std::io;
 use std::path::Path;
+use std::result;
 use std::str;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use rustc_serialize::Decodable;
 
 use {
     ByteString, Result, Decoded,
-    Error, LocatableError, ParseError,
+    Error, LocatableError, ParseError, Utf16Transcoder, ChunkReader,
 };
 
 use self::State::*;
 
 const BUF_SIZE: usize = 1024 * 128;
 
+// The callback given to `Reader::on_header`.
+type HeaderCallback = Box<FnMut(usize, &str) + Send>;
+
+// The callback given to `Reader::error_sink`.
+type ErrorSink = Box<FnMut(&Error) + Send>;
+
 /// A record terminator.
 ///
 /// Ideally, this would just be a `u8` like any other delimiter, but a useful
@@ -21,12 +33,25 @@ const BUF_SIZE: usize = 1024 * 128;
 ///
 /// Generally, you won't need to use this type because `CRLF` is the default,
 /// which is by far the most widely used record terminator.
+///
+/// Note that more variants may be added in the future (as `None` was), so
+/// a `match` on this type should include a wildcard arm.
 #[derive(Clone, Copy)]
 pub enum RecordTerminator {
     /// Parses `\r`, `\n` or `\r\n` as a single record terminator.
     CRLF,
     /// Parses the byte given as a record terminator.
     Any(u8),
+    /// No byte terminates a record, so all of the data is a single record.
+    ///
+    /// Line terminators are then just data, even in unquoted fields. Note
+    /// that `has_headers` is enabled by default, which makes that single
+    /// record the header row, so the record iterators yield nothing unless
+    /// `has_headers` is disabled.
+    ///
+    /// When writing, nothing is written after a record, so writing a second
+    /// record returns an error instead of running the two together.
+    None,
 }
 
 impl RecordTerminator {
@@ -34,7 +59,7 @@ impl RecordTerminator {
     fn is_crlf(&self) -> bool {
         match *self {
             RecordTerminator::CRLF => true,
-            RecordTerminator::Any(_) => false,
+            RecordTerminator::Any(_) | RecordTerminator::None => false,
         }
     }
 }
@@ -44,11 +69,55 @@ impl PartialEq<u8> for RecordTerminator {
     fn eq(&self, &other: &u8) -> bool {
         match *self {
             RecordTerminator::CRLF => other == b'\r' || other == b'\n',
-            RecordTerminator::Any(b) => other == b
+            RecordTerminator::Any(b) => other == b,
+            RecordTerminator::None => false,
         }
     }
 }
 
+/// Where the escape character set with `Reader::escape` applies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscapeMode {
+    /// The escape character is only recognized inside quoted fields.
+    QuotedOnly,
+    /// The escape character is recognized in every field, so that it can
+    /// also escape delimiters, quotes and record terminators in unquoted
+    /// fields.
+    Everywhere,
+}
+
+/// The value of a column added with `Reader::add_column`.
+pub enum ColumnSource {
+    /// The same value for every record.
+    Value(String),
+    /// The number of the record, where the first row of the CSV data is
+    /// record `1`.
+    RecordNumber,
+    /// The byte offset at which the record starts.
+    ByteOffset,
+    /// A value computed from the fields of the record.
+    With(ColumnFn),
+}
+
+/// A function that computes the value of a column from the fields of a
+/// record, as used by `ColumnSource::With`.
+pub type ColumnFn = Box<FnMut(&[ByteString]) -> ByteString + Send>;
+
+/// How the record iterators handle malformed records.
+///
+/// This is set with `Reader::on_error`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnError {
+    /// Yield the first error and then stop.
+    Abort,
+    /// Silently drop malformed records and continue with the next one.
+    Skip,
+    /// Like `Skip`, except fields that aren't valid UTF-8 are converted
+    /// lossily (with `String::from_utf8_lossy`) instead of dropping their
+    /// record.
+    Lossy,
+}
+
 /// A CSV reader.
 ///
 /// This reader parses CSV data and exposes records via iterators.
@@ -104,13 +173,81 @@ pub struct Reader<R> {
     first_row_done: bool,
     irecord: u64,
     ifield: u64,
+    // The record terminator that ended the last record.
+    term: [u8; 2],
+    term_len: usize,
+    // Set when a record ended in a `\r` at the very end of the buffer, so
+    // that a `\n` at the start of the next buffer completes the CRLF.
+    cr_pending: bool,
     byte_offset: u64,
+    // Bytes parsed before the last seek, and the offset seeked to. These
+    // make up `total_bytes_read`.
+    bytes_before_seek: u64,
+    seek_offset: u64,
     delimiter: u8,
     quote: u8,
+    close_quote: u8,
+    alt_quote: Option<u8>,
+    // The quote that closes the quoted field being parsed.
+    cur_close: u8,
+    comment: Option<u8>,
     escape: Option<u8>,
+    escape_mode: EscapeMode,
     double_quote: bool,
+    preserve_quotes: bool,
+    relaxed_quotes: bool,
+    capture_raw: bool,
+    // The bytes of the current record as they appear in the CSV data, kept
+    // when `capture_raw` is set. The bytes in `buf` from `raw_from` up to
+    // `bufi` haven't been copied yet.
+    raw_bytes: Vec<u8>,
+    raw_from: usize,
+    // Whether `raw_bytes` (and `raw_record`) hold a complete record.
+    raw_done: bool,
+    strict_escape: bool,
+    trailing_quote_error: bool,
     record_term: RecordTerminator,
     flexible: bool,
+    header_excluded: bool,
+    // The length of the first data record, when `header_excluded` is set.
+    data_len: Option<u64>,
+    on_error: Option<OnError>,
+    max_field_lines: Option<u64>,
+    max_fields: Option<u64>,
+    read_limit: Option<u64>,
+    // The value of `total_bytes_read` that `read_limit` counts from.
+    read_limit_base: u64,
+    field_lines: u64,
+    trim_chars: Vec<u8>,
+    null_value: Option<ByteString>,
+    ignore_blank_lines: bool,
+    leading_width: usize,
+    leading: Vec<u8>,
+    // Whether the current field has quotes or escapes in it.
+    field_quoted: bool,
+    // Whether the current field starts with a quote.
+    field_starts_quoted: bool,
+    track_quotes: bool,
+    record_quoted: Vec<bool>,
+    // `record_quoted` lined up with the fields after `project` and
+    // `add_column` were applied.
+    shaped_quoted: Vec<bool>,
+    progress: Option<Arc<AtomicUsize>>,
+    // The value of `total_bytes_read` last added to `progress`.
+    progress_base: u64,
+    // The fields of the current record, kept when `capture_raw` is set.
+    raw_record: Vec<ByteString>,
+    projection: Option<Vec<usize>>,
+    added: Vec<(ByteString, ColumnSource)>,
+    // The byte offsets at which the current and the first record start.
+    record_start: u64,
+    first_row_start: u64,
+    projection_strict: bool,
+    header_line: u64,
+    on_header: Option<HeaderCallback>,
+    error_sink: Option<ErrorSink>,
+    // The number of lines left to skip before the first record.
+    skip_lines: u64,
 
     // When this is true, the first record is interpreted as a "header" row.
     // This is opaque to the raw iterator, but is used in any iterator that
@@ -119,13 +256,29 @@ pub struct Reader<R> {
     // TODO: This is exposed for use in the `index` sub-module. Is that OK?
     #[doc(hidden)]
     pub has_headers: bool,
-    has_seeked: bool,
+    // When this is true, the record iterators no longer need to check
+    // whether the first record should be returned. (It is also set after
+    // seeking, since the first record is then somewhere else entirely.)
+    first_handled: bool,
+    // A record that has been read ahead of the record iterators.
+    peeked: Option<Vec<ByteString>>,
+    // The record last read by the record iterators as it was parsed, i.e.,
+    // before `project` and `add_column` were applied. This is only kept
+    // while either of them is in use, so that a record that was read ahead
+    // can still become the header row.
+    unshaped: Option<Vec<ByteString>>,
+    // The bounds of the part of the current field in `fieldbuf` that has
+    // not yet been handed to `read_record_into`.
+    into_field: Option<(usize, usize)>,
 }
 
 impl<R: io::Read> Reader<R> {
     /// Creates a new CSV reader from an arbitrary `io::Read`.
     ///
-    /// The reader is buffered for you automatically.
+    /// The reader is buffered for you automatically. Namely, the parser
+    /// reads directly from `rdr` into its own internal buffer, so there is no
+    /// intermediate `io::BufReader`. Wrapping `rdr` in an `io::BufReader`
+    /// yourself is therefore unnecessary and only adds an extra copy.
     pub fn from_reader(rdr: R) -> Reader<R> {
         Reader {
             rdr: rdr,
@@ -137,20 +290,126 @@ impl<R: io::Read> Reader<R> {
             first_row: vec![],
             first_row_done: false,
             irecord: 1,
-            ifield: 1,
+            ifield: 0,
+            term: [0; 2],
+            term_len: 0,
+            cr_pending: false,
             byte_offset: 0,
+            bytes_before_seek: 0,
+            seek_offset: 0,
             delimiter: b',',
             quote: b'"',
+            close_quote: b'"',
+            alt_quote: None,
+            cur_close: b'"',
+            comment: None,
             escape: None,
+            escape_mode: EscapeMode::QuotedOnly,
             double_quote: true,
+            preserve_quotes: false,
+            relaxed_quotes: false,
+            capture_raw: false,
+            raw_bytes: vec![],
+            raw_from: 0,
+            raw_done: false,
+            strict_escape: false,
+            trailing_quote_error: false,
             record_term: RecordTerminator::CRLF,
             flexible: false,
+            header_excluded: false,
+            data_len: None,
+            on_error: None,
+            max_field_lines: None,
+            max_fields: None,
+            read_limit: None,
+            read_limit_base: 0,
+            field_lines: 0,
+            trim_chars: vec![],
+            null_value: None,
+            ignore_blank_lines: false,
+            leading_width: 0,
+            leading: vec![],
+            field_quoted: false,
+            field_starts_quoted: false,
+            track_quotes: false,
+            record_quoted: vec![],
+            shaped_quoted: vec![],
+            progress: None,
+            progress_base: 0,
+            raw_record: vec![],
+            projection: None,
+            added: vec![],
+            record_start: 0,
+            first_row_start: 0,
+            projection_strict: false,
+            header_line: 0,
+            on_header: None,
+            error_sink: None,
+            skip_lines: 0,
             has_headers: true,
-            has_seeked: false,
+            first_handled: false,
+            peeked: None,
+            unshaped: None,
+            into_field: None,
         }
     }
 }
 
+impl<R: io::Read> Reader<Utf16Transcoder<R>> {
+    /// Creates a new CSV reader that transcodes UTF-16 data to UTF-8.
+    ///
+    /// If the data starts with a UTF-16 byte order mark (`FF FE` or
+    /// `FE FF`), then the mark is removed and the rest of the data is
+    /// transcoded to UTF-8 before it is parsed. Otherwise, the data is
+    /// parsed as is. This is useful for the UTF-16 tab-delimited files that
+    /// some spreadsheet programs export as "Unicode Text".
+    ///
+    /// Since the parser only sees the transcoded data, all byte offsets
+    /// (e.g., from `byte_offset`) refer to the transcoded UTF-8 bytes.
+    /// Seeking is not supported.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// // "a\tb\n" as UTF-16LE, with a byte order mark.
+    /// let data = b"\xFF\xFEa\x00\t\x00b\x00\n\x00";
+    /// let mut rdr = csv::Reader::from_reader_utf16(&data[..])
+    ///                           .has_headers(false)
+    ///                           .delimiter(b'\t');
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![vec!["a", "b"]]);
+    /// ```
+    pub fn from_reader_utf16(rdr: R) -> Reader<Utf16Transcoder<R>> {
+        Reader::from_reader(Utf16Transcoder::new(rdr))
+    }
+}
+
+impl<I: Iterator<Item=io::Result<Vec<u8>>>> Reader<ChunkReader<I>> {
+    /// Creates a new CSV reader over a sequence of byte chunks.
+    ///
+    /// This is useful when the data arrives in pieces (e.g., from a channel)
+    /// rather than from a single `io::Read`. Chunk boundaries can fall
+    /// anywhere, including in the middle of a record or field.
+    ///
+    /// If the iterator yields an error, then it is reported as an
+    /// `Error::Io` by the reader.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let chunks = vec![
+    ///     Ok(b"a,b\n1,fo".to_vec()),
+    ///     Ok(b"o\n2,bar\n".to_vec()),
+    /// ];
+    /// let mut rdr = csv::Reader::from_chunks(chunks.into_iter());
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![vec!["1", "foo"], vec!["2", "bar"]]);
+    /// ```
+    pub fn from_chunks(chunks: I) -> Reader<ChunkReader<I>> {
+        Reader::from_reader(ChunkReader::new(chunks))
+    }
+}
+
 impl Reader<fs::File> {
     /// Creates a new CSV reader for the data at the file path given.
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Reader<fs::File>> {
@@ -247,6 +506,10 @@ impl<R: io::Read> Reader<R> {
     /// # }
     /// ```
     ///
+    /// When a whole record is decoded into a tuple, the record must have
+    /// exactly as many fields as the tuple has elements. A record with fewer
+    /// or more fields results in a decoding error.
+    ///
     /// Finally, as a special case, a tuple/struct/`Vec` can be used as the
     /// "tail" of another tuple/struct/`Vec` to capture all remaining fields:
     ///
@@ -271,6 +534,20 @@ impl<R: io::Read> Reader<R> {
     /// # }
     /// ```
     ///
+    /// Fixed-size arrays (of up to `32` elements) can be used too. This is
+    /// convenient when every record is known to have exactly `N` fields,
+    /// since a record with any other number of fields results in a decoding
+    /// error:
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,b,c\n1,2,3\n4,5")
+    ///                           .flexible(true);
+    /// let mut rows = rdr.decode::<[String; 3]>();
+    ///
+    /// assert_eq!(rows.next().unwrap().unwrap(), ["1", "2", "3"]);
+    /// assert!(rows.next().unwrap().is_err());
+    /// ```
+    ///
     /// If a tuple/struct/`Vec` appears any where other than the "tail" of a
     /// record, then the behavior is undefined. (You'll likely get a runtime
     /// error. I believe this is a limitation of the current decoding machinery
@@ -286,6 +563,12 @@ impl<R: io::Read> Reader<R> {
     /// Returns an iterator of records in the CSV data where each field is
     /// a `String`.
     ///
+    /// If a record is not valid UTF-8, then an error is yielded for that
+    /// record. Since the record has already been parsed in full by the time
+    /// it is converted, the parser is positioned at the start of the next
+    /// record (as reported by `byte_offset`), so iteration may safely
+    /// continue past the error.
+    ///
     /// ### Example
     ///
     /// This is your standard CSV interface with no type decoding magic.
@@ -308,11 +591,413 @@ impl<R: io::Read> Reader<R> {
         StringRecords { p: self.byte_records() }
     }
 
+    /// Returns an iterator of `String` records that starts with the header
+    /// row.
+    ///
+    /// If `has_headers` is enabled and no records have been read yet, then
+    /// the header row is yielded first, exactly as `headers` returns it.
+    /// The data records follow. (If `has_headers` is disabled, then this is
+    /// the same as `records`, which already yields the first row.)
+    ///
+    /// Unlike disabling `has_headers`, this keeps the header row cached, so
+    /// `headers` still works as usual. This is convenient for tools that
+    /// pass all rows through unchanged.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2");
+    /// let rows = rdr.records_with_headers()
+    ///               .collect::<csv::Result<Vec<_>>>().unwrap();
+    /// assert_eq!(rows, vec![vec!["a", "b"], vec!["1", "2"]]);
+    /// assert_eq!(rdr.headers().unwrap(), vec!["a", "b"]);
+    /// ```
+    pub fn records_with_headers<'a>(
+        &'a mut self,
+    ) -> RecordsWithHeaders<'a, R> {
+        let header = self.has_headers && !self.first_handled;
+        RecordsWithHeaders { p: self.records(), header: header }
+    }
+
+    /// Reads all remaining records into a `Vec`.
+    ///
+    /// This is a convenience for
+    /// `rdr.records().collect::<csv::Result<Vec<_>>>()`. Like that
+    /// expression, this stops reading at the first error and returns it.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2\n3,4");
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![vec!["1", "2"], vec!["3", "4"]]);
+    /// ```
+    pub fn collect_records(&mut self) -> Result<Vec<Vec<String>>> {
+        self.records().collect()
+    }
+
+    /// Reads all remaining records into columns.
+    ///
+    /// This returns `n_cols` vectors, where the `i`th vector contains the
+    /// `i`th field of every record. Fields are appended to their column as
+    /// they are parsed, so no intermediate row is built for each record.
+    ///
+    /// If a record doesn't have exactly `n_cols` fields, then an
+    /// `UnequalLengths` error is returned, unless `flexible` is enabled. In
+    /// that case, short records are padded with empty fields and any extra
+    /// fields are ignored, so that all columns have the same length.
+    ///
+    /// The header row is skipped if `has_headers` is enabled. (Note that
+    /// `project` and `add_column` do not apply here.)
+    ///
+    /// Since all of the data is held in memory at once, this needs memory
+    /// proportional to the size of the CSV data, plus one `String` per field.
+    /// Every column grows independently, so wide data results in many
+    /// (smaller) allocations while tall data results in few (larger) ones.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("name,age\nfoo,5\nbar,7");
+    /// let cols = rdr.read_columns(2).unwrap();
+    ///
+    /// assert_eq!(cols[0], vec!["foo", "bar"]);
+    /// assert_eq!(cols[1], vec!["5", "7"]);
+    /// ```
+    pub fn read_columns(&mut self, n_cols: usize) -> Result<Vec<Vec<String>>> {
+        let mut cols = vec![vec![]; n_cols];
+        if let Some(record) = self.pending_record() {
+            // The record may have been read ahead by the record iterators,
+            // so use it as parsed rather than as they would return it.
+            let record = try!(record);
+            let record = self.unshaped.take().unwrap_or(record);
+            let record = try!(byte_record_to_utf8(record));
+            let len = record.len();
+            for (col, field) in cols.iter_mut().zip(record) {
+                col.push(field);
+            }
+            let irecord = self.irecord - 1;
+            try!(self.end_column_row(&mut cols, len, irecord));
+        }
+        let mut i = 0;
+        loop {
+            match self.next_str() {
+                NextField::EndOfCsv => break,
+                NextField::Error(err) => return Err(err),
+                NextField::EndOfRecord => {
+                    let irecord = self.irecord - 1;
+                    try!(self.end_column_row(&mut cols, i, irecord));
+                    i = 0;
+                }
+                NextField::Data(field) => {
+                    if let Some(col) = cols.get_mut(i) {
+                        col.push(field.to_owned());
+                    }
+                    i += 1;
+                }
+            }
+        }
+        Ok(cols)
+    }
+
+    /// Reads the next record into a fixed-size array of fields.
+    ///
+    /// This is convenient when every record is known to have exactly `N`
+    /// fields, since the fields can then be destructured or indexed without
+    /// bounds checks. The record is read just like `records` would read it
+    /// (so `project` and `add_column` apply), and if it doesn't have exactly
+    /// `N` fields, then an `UnequalLengths` error is returned. This is the
+    /// case even if `flexible` is enabled.
+    ///
+    /// `None` is returned if there are no more records. Arrays of up to
+    /// `32` fields are supported.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("name,age\nfoo,5\nbar")
+    ///                           .flexible(true);
+    ///
+    /// let row: [String; 2] = rdr.read_record_array().unwrap().unwrap();
+    /// assert_eq!(row, ["foo", "5"]);
+    /// assert!(rdr.read_record_array::<[String; 2]>().is_err());
+    /// ```
+    pub fn read_record_array<A: RecordArray>(&mut self) -> Result<Option<A>> {
+        let record = match self.next_byte_record() {
+            None => return Ok(None),
+            Some(record) => try!(record),
+        };
+        let record = try!(byte_record_to_utf8(record));
+        if record.len() != A::len() {
+            return Err(Error::Parse(LocatableError {
+                record: self.irecord - 1,
+                field: record.len() as u64,
+                err: ParseError::UnequalLengths {
+                    expected: A::len() as u64,
+                    got: record.len() as u64,
+                },
+            }));
+        }
+        Ok(Some(A::from_record(record)))
+    }
+
+    /// Checks the length of a record read by `read_columns`, padding its
+    /// columns if the record was too short.
+    fn end_column_row(
+        &self,
+        cols: &mut [Vec<String>],
+        len: usize,
+        irecord: u64,
+    ) -> Result<()> {
+        if len != cols.len() && !self.flexible {
+            return Err(Error::Parse(LocatableError {
+                record: irecord,
+                field: len as u64,
+                err: ParseError::UnequalLengths {
+                    expected: cols.len() as u64,
+                    got: len as u64,
+                },
+            }));
+        }
+        for col in cols.iter_mut().skip(len) {
+            col.push(String::new());
+        }
+        Ok(())
+    }
+
+    /// Skips leading records for which `pred` returns `true`.
+    ///
+    /// The first record for which `pred` returns `false` is left unread, so
+    /// that it is the next record returned by any of the record iterators.
+    /// The number of records skipped is returned.
+    ///
+    /// If an error occurs while reading a record, then it is returned and
+    /// the offending record is skipped.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "# notes\n# more notes\na,b\n1,2";
+    /// let mut rdr = csv::Reader::from_string(data)
+    ///                           .has_headers(false)
+    ///                           .flexible(true);
+    ///
+    /// let skipped = rdr.skip_records_while(|r| r[0].starts_with("#"));
+    /// assert_eq!(skipped.unwrap(), 2);
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![vec!["a", "b"], vec!["1", "2"]]);
+    /// ```
+    pub fn skip_records_while<P>(&mut self, mut pred: P) -> Result<u64>
+            where P: FnMut(&[String]) -> bool {
+        let mut skipped = 0;
+        loop {
+            let record = match self.next_byte_record() {
+                None => return Ok(skipped),
+                Some(record) => try!(record),
+            };
+            let record = try!(byte_record_to_utf8(record));
+            if !pred(&record) {
+                self.put_back(record);
+                return Ok(skipped);
+            }
+            skipped += 1;
+        }
+    }
+
+    /// Returns an iterator of `String` records that stops at the first
+    /// record for which `pred` returns `false`.
+    ///
+    /// That first record is *not* consumed. It is left unread, so that it
+    /// is the next record returned by any of the record iterators.
+    /// Malformed records are handled just like `records` handles them (see
+    /// `on_error`).
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "a,1\nb,2\n,\nc,3";
+    /// let mut rdr = csv::Reader::from_string(data).has_headers(false);
+    ///
+    /// let rows = rdr.take_records_while(|r| !r[0].is_empty())
+    ///               .collect::<csv::Result<Vec<_>>>().unwrap();
+    /// assert_eq!(rows, vec![vec!["a", "1"], vec!["b", "2"]]);
+    ///
+    /// let rest = rdr.collect_records().unwrap();
+    /// assert_eq!(rest, vec![vec!["", ""], vec!["c", "3"]]);
+    /// ```
+    pub fn take_records_while<'a, P>(
+        &'a mut self,
+        pred: P,
+    ) -> TakeRecordsWhile<'a, R, P>
+            where P: FnMut(&[String]) -> bool {
+        TakeRecordsWhile { p: self.records(), pred: pred, done: false }
+    }
+
+    /// Returns an iterator of `String` records that skips adjacent
+    /// duplicate records.
+    ///
+    /// A record is yielded only if it differs from the record yielded just
+    /// before it. Only that one previous record is kept in memory, so this
+    /// only removes *adjacent* duplicates. (To remove all duplicates from
+    /// sorted data, sort it first.)
+    ///
+    /// Errors are passed through and do not affect which record is
+    /// considered the previous one.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "a,1\na,1\nb,2\na,1";
+    ///
+    /// let mut rdr = csv::Reader::from_string(data).has_headers(false);
+    /// let rows = rdr.dedup_records().collect::<csv::Result<Vec<_>>>()
+    ///                               .unwrap();
+    /// assert_eq!(rows, vec![vec!["a", "1"], vec!["b", "2"], vec!["a", "1"]]);
+    /// ```
+    pub fn dedup_records<'a>(&'a mut self) -> DedupRecords<'a, R> {
+        DedupRecords { p: self.records(), prev: None }
+    }
+
+    /// Returns an iterator of overlapping pairs of consecutive `String`
+    /// records.
+    ///
+    /// Each item is a pair `(previous, current)`, so `n` records yield
+    /// `n - 1` pairs, and a single record yields nothing. This is handy for
+    /// computing differences between consecutive rows. Only one record is
+    /// held back at a time, and it is cloned for each pair.
+    ///
+    /// Errors are passed through and do not affect which record is
+    /// considered the previous one.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "1\n3\n6";
+    /// let mut rdr = csv::Reader::from_string(data).has_headers(false);
+    /// let deltas: Vec<i32> = rdr.windows2().map(|pair| {
+    ///     let (prev, cur) = pair.unwrap();
+    ///     cur[0].parse::<i32>().unwrap() - prev[0].parse::<i32>().unwrap()
+    /// }).collect();
+    /// assert_eq!(deltas, vec![2, 3]);
+    /// ```
+    pub fn windows2<'a>(&'a mut self) -> Windows2<'a, R> {
+        Windows2 { p: self.records(), prev: None }
+    }
+
+    /// Returns an iterator of groups of consecutive `String` records that
+    /// have the same key.
+    ///
+    /// The key of each record is computed with `key`. Consecutive records
+    /// with equal keys are collected into a group, and each group is
+    /// yielded along with its key once a record with a different key (or
+    /// the end of the data) is reached. Only the current group is kept in
+    /// memory.
+    ///
+    /// Since only *adjacent* records are grouped, the data should be sorted
+    /// by the key to get one group per key (like `GROUP BY` in SQL).
+    ///
+    /// Errors are passed through as soon as they happen and don't end the
+    /// current group.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "city,sales\nOslo,1\nOslo,2\nRome,5";
+    /// let mut rdr = csv::Reader::from_string(data);
+    /// let totals: Vec<(String, u32)> = rdr.group_by(|r| r[0].clone())
+    ///     .map(|group| {
+    ///         let (city, rows) = group.unwrap();
+    ///         let total = rows.iter().map(|r| r[1].parse::<u32>().unwrap())
+    ///                                .sum();
+    ///         (city, total)
+    ///     })
+    ///     .collect();
+    /// assert_eq!(totals, vec![("Oslo".to_string(), 3),
+    ///                         ("Rome".to_string(), 5)]);
+    /// ```
+    pub fn group_by<'a, K, F>(&'a mut self, key: F) -> GroupBy<'a, R, K, F>
+            where K: Eq, F: FnMut(&[String]) -> K {
+        GroupBy { p: self.records(), key: key, group: None }
+    }
+
+    /// Returns an iterator of `String` records that skips records whose
+    /// field at index `col` is the same as in the record before it.
+    ///
+    /// This is like `dedup_records`, except only one column is compared.
+    /// Only the key of the previously yielded record is kept in memory, so
+    /// this only removes *consecutive* duplicates. (To remove all
+    /// duplicates, the data must be sorted by that column.)
+    ///
+    /// A record that has no field at index `col` is always yielded.
+    ///
+    /// Errors are passed through and do not affect which record is
+    /// considered the previous one.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "a,1\na,2\nb,3\na,4";
+    ///
+    /// let mut rdr = csv::Reader::from_string(data).has_headers(false);
+    /// let rows = rdr.dedup_consecutive_by(0)
+    ///               .collect::<csv::Result<Vec<_>>>().unwrap();
+    /// assert_eq!(rows, vec![vec!["a", "1"], vec!["b", "3"], vec!["a", "4"]]);
+    /// ```
+    pub fn dedup_consecutive_by<'a>(
+        &'a mut self,
+        col: usize,
+    ) -> DedupRecordsBy<'a, R> {
+        DedupRecordsBy {
+            p: self.records(),
+            col: col,
+            prev: String::new(),
+            has_prev: false,
+        }
+    }
+
+    /// Returns a cursor over `String` records that can look ahead.
+    ///
+    /// The cursor keeps the current record along with up to `n` of the
+    /// records that follow it, so that each record can be processed with
+    /// some knowledge of what comes next (e.g., to fill forward missing
+    /// values). At most `n + 1` records are kept in memory at any given
+    /// time.
+    ///
+    /// Records are read just like `records` reads them. Namely, the header
+    /// row is skipped if `has_headers` is enabled.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a\n1\n2").has_headers(false);
+    /// let mut look = rdr.with_lookahead(1);
+    ///
+    /// assert!(look.advance().unwrap());
+    /// assert_eq!(look.current().unwrap(), &["a".to_string()][..]);
+    /// assert_eq!(look.peek(0).unwrap(), &["1".to_string()][..]);
+    /// ```
+    pub fn with_lookahead<'a>(
+        &'a mut self,
+        n: usize,
+    ) -> LookaheadReader<'a, R> {
+        LookaheadReader {
+            p: self.records(),
+            n: n,
+            buf: VecDeque::with_capacity(n + 1),
+            started: false,
+            done: false,
+            err: None,
+        }
+    }
+
     /// Returns a *copy* of the first record in the CSV data as strings.
     ///
     /// This method may be called at any time and regardless of whether
     /// `has_headers` is set or not.
     ///
+    /// If the CSV data is empty, then an empty record (with zero fields) is
+    /// returned instead of an error, and all record iterators are done.
+    ///
     /// ### Example
     ///
     /// ```rust
@@ -352,6 +1037,89 @@ impl<R: io::Read> Reader<R> {
     pub fn headers(&mut self) -> Result<Vec<String>> {
         byte_record_to_utf8(try!(self.byte_headers()))
     }
+
+    /// Forgets the header row, so that the next record becomes the new one.
+    ///
+    /// This is useful for files that contain several tables one after the
+    /// other, each with its own header row. Once the records of one table
+    /// have been read, call this to read the header row of the next table
+    /// with `headers` (and to have the record iterators skip it, if
+    /// `has_headers` is enabled).
+    ///
+    /// If a record has already been read ahead (e.g., with
+    /// `peek_first_field` or `take_records_while`), then that record becomes
+    /// the new header row. It is used as it was parsed, so `project` and
+    /// `add_column` are applied to it only once. (A record pushed back with
+    /// `unread_record` is used as given instead.)
+    ///
+    /// Unless `flexible` is enabled, the records after the new header row
+    /// must have the same length as it.
+    ///
+    /// This can also be used to read the header row again after seeking
+    /// back to the start of the data with `seek(0)`. (Unlike
+    /// `seek_to_start`, which keeps the cached header row.) Note that
+    /// `seek` assumes that it lands past the header row, so
+    /// `clear_headers` must be called after seeking, not before.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "a,b\n1,2\n--\nx,y,z\n3,4,5";
+    /// let mut rdr = csv::Reader::from_string(data).flexible(true);
+    ///
+    /// let rows = rdr.take_records_while(|r| r[0] != "--")
+    ///               .collect::<csv::Result<Vec<_>>>().unwrap();
+    /// assert_eq!(rows, vec![vec!["1", "2"]]);
+    /// rdr.skip_records_while(|r| r[0] == "--").unwrap();
+    ///
+    /// rdr.clear_headers();
+    /// assert_eq!(rdr.headers().unwrap(), vec!["x", "y", "z"]);
+    /// assert_eq!(rdr.collect_records().unwrap(), vec![vec!["3", "4", "5"]]);
+    /// ```
+    pub fn clear_headers(&mut self) {
+        self.first_handled = false;
+        self.data_len = None;
+        match self.peeked.take() {
+            Some(record) => {
+                self.first_row = self.unshaped.take().unwrap_or(record);
+                self.first_row_done = true;
+            }
+            None => {
+                self.first_row.clear();
+                self.first_row_done = false;
+            }
+        }
+    }
+
+    /// Reads the headers and returns them along with an iterator that owns
+    /// this reader and yields `String` records.
+    ///
+    /// This is useful when you need to refer to the headers while iterating
+    /// over records, since the headers are owned separately from the
+    /// iterator. The headers are exactly what `headers` returns, and the
+    /// iterator yields exactly what `records` would. (So if `has_headers` is
+    /// disabled, then the first record is both returned as the headers and
+    /// yielded by the iterator.)
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let rdr = csv::Reader::from_string("name,age\nfoo,1\nbar,2");
+    ///
+    /// let (headers, records) = rdr.into_records_with_headers().unwrap();
+    /// for record in records {
+    ///     let record = record.unwrap();
+    ///     assert_eq!(headers.len(), record.len());
+    ///     println!("{}: {}, {}: {}",
+    ///              headers[0], record[0], headers[1], record[1]);
+    /// }
+    /// ```
+    pub fn into_records_with_headers(
+        mut self,
+    ) -> Result<(Vec<String>, StringRecordsIntoIter<R>)> {
+        let headers = try!(self.headers());
+        Ok((headers, StringRecordsIntoIter { p: self, errored: false }))
+    }
 }
 
 impl<R: io::Read> Reader<R> {
@@ -380,642 +1148,3819 @@ impl<R: io::Read> Reader<R> {
         self
     }
 
-    /// Whether to allow flexible length records when reading CSV data.
+    /// Guesses whether the CSV data in `sample` starts with a header row.
     ///
-    /// When this is set to `true`, records in the CSV data can have different
-    /// lengths. By default, this is disabled, which will cause the CSV reader
-    /// to return an error if it tries to read a record that has a different
-    /// length than the first record that it read.
-    pub fn flexible(mut self, yes: bool) -> Reader<R> {
-        self.flexible = yes;
-        self
+    /// `sample` should be the first few rows of the data, e.g., its first
+    /// few kilobytes. It is parsed with this reader's delimiter, quote,
+    /// escape and record terminator settings. The result can be passed to
+    /// `has_headers`, which otherwise isn't affected by this method.
+    ///
+    /// The heuristic is that the first row is a header row if none of its
+    /// fields is a number while, in at least one column, every non-empty
+    /// field of the following rows is a number. If the sample doesn't end
+    /// with a record terminator, then its last row is assumed to be cut
+    /// short and is ignored.
+    ///
+    /// This is only a guess. It can't recognize headers of data that
+    /// doesn't have any numeric columns, and it is fooled by headers that
+    /// are numbers (e.g., years). At least two complete rows are needed,
+    /// so `false` is returned for shorter samples.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "name,age\nAda,36\nAlan,41\n";
+    /// let rdr = csv::Reader::from_string(data);
+    /// let guess = rdr.detect_headers(data.as_bytes());
+    /// assert!(guess);
+    ///
+    /// let mut rdr = rdr.has_headers(guess);
+    /// assert_eq!(rdr.collect_records().unwrap().len(), 2);
+    /// ```
+    pub fn detect_headers(&self, sample: &[u8]) -> bool {
+        let mut rdr = Reader::from_bytes(sample)
+                             .has_headers(false)
+                             .flexible(true)
+                             .delimiter(self.delimiter)
+                             .quote_pair(self.quote, self.close_quote)
+                             .alt_quote(self.alt_quote)
+                             .comment(self.comment)
+                             .escape(self.escape)
+                             .escape_mode(self.escape_mode)
+                             .double_quote(self.double_quote)
+                             .record_terminator(self.record_term)
+                             .trim_chars(&self.trim_chars);
+        let mut rows = vec![];
+        for row in rdr.byte_records() {
+            match row {
+                Ok(row) => rows.push(row),
+                Err(_) => break,
+            }
+        }
+        // A sample that doesn't end with a record terminator probably cuts
+        // its last record short, so that record is ignored.
+        match sample.last() {
+            Some(&b) if self.record_term == b => {}
+            _ => { rows.pop(); }
+        }
+        if rows.len() < 2 {
+            return false;
+        }
+        let (header, data) = (&rows[0], &rows[1..]);
+        if header.iter().all(|f| f.is_empty())
+            || header.iter().any(|f| is_numeric(f)) {
+            return false;
+        }
+        (0..header.len()).any(|i| {
+            let mut fields = data.iter()
+                                 .filter_map(|row| row.get(i))
+                                 .filter(|f| !f.is_empty())
+                                 .peekable();
+            fields.peek().is_some() && fields.all(|f| is_numeric(f))
+        })
     }
 
-    /// Set the record terminator to use when reading CSV data.
+    /// Set the line (starting at `0`) on which the header row is found.
     ///
-    /// In the vast majority of situations, you'll want to use the default
-    /// value, `RecordTerminator::CRLF`, which automatically handles `\r`,
-    /// `\n` or `\r\n` as record terminators. (Notably, this is a special
-    /// case since two characters can correspond to a single terminator token.)
+    /// Every line before it is skipped, regardless of its contents. This
+    /// is useful for data that starts with a few lines of notes or
+    /// summaries before the actual CSV data. All lines after the header row
+    /// are data.
     ///
-    /// However, you may use `RecordTerminator::Any` to specify any ASCII
-    /// character to use as the record terminator. For example, you could
-    /// use `RecordTerminator::Any(b'\n')` to only accept line feeds as
-    /// record terminators, or `b'\x1e'` for the ASCII record separator.
-    pub fn record_terminator(mut self, term: RecordTerminator) -> Reader<R> {
-        self.record_term = term;
+    /// Lines are separated by the record terminator, and every line counts,
+    /// including empty ones. Lines are only skipped at the start of the
+    /// data (or after `seek_to_start`), never after seeking elsewhere.
+    ///
+    /// This enables `has_headers`. If `has_headers` is disabled afterwards,
+    /// then the line given is read as the first record instead.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "exported 2016-01-01\n\nname,age\nfoo,5";
+    /// let mut rdr = csv::Reader::from_string(data).header_line(2);
+    ///
+    /// assert_eq!(rdr.headers().unwrap(), vec!["name", "age"]);
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![vec!["foo", "5"]]);
+    /// ```
+    pub fn header_line(mut self, line: u64) -> Reader<R> {
+        self.header_line = line;
+        self.skip_lines = line;
+        self.has_headers = true;
         self
     }
 
-    /// Set the quote character to use when reading CSV data.
+    /// Set a function to call for every field of the header row.
     ///
-    /// Since the CSV reader is meant to be mostly encoding agnostic, you must
-    /// specify the quote as a single ASCII byte. For example, to read
-    /// single quoted data, you would use `b'\''`.
+    /// The function is called with the index (starting at `0`) and value of
+    /// each header, once the header row has been parsed. It is called at
+    /// most once per header, no matter how many times the headers are
+    /// requested, and never for data records. Nothing is called when
+    /// `has_headers` is disabled.
     ///
-    /// The default value is `b'"'`.
+    /// Headers that are not valid UTF-8 are converted lossily (with
+    /// `String::from_utf8_lossy`) before they are passed to the function.
     ///
-    /// If `quote` is `None`, then no quoting will be used.
-    pub fn quote(mut self, quote: u8) -> Reader<R> {
-        self.quote = quote;
+    /// ### Example
+    ///
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let seen = Arc::new(Mutex::new(vec![]));
+    /// let seen2 = seen.clone();
+    /// let mut rdr = csv::Reader::from_string("name,age\nfoo,5")
+    ///                           .on_header(move |_, name| {
+    ///     seen2.lock().unwrap().push(name.to_string());
+    /// });
+    /// rdr.collect_records().unwrap();
+    ///
+    /// assert_eq!(*seen.lock().unwrap(), vec!["name", "age"]);
+    /// ```
+    pub fn on_header<F>(mut self, f: F) -> Reader<R>
+            where F: FnMut(usize, &str) + Send + 'static {
+        self.on_header = Some(Box::new(f));
         self
     }
 
-    /// Set the escape character to use when reading CSV data.
+    /// Whether to allow flexible length records when reading CSV data.
     ///
-    /// Since the CSV reader is meant to be mostly encoding agnostic, you must
-    /// specify the escape as a single ASCII byte.
+    /// When this is set to `true`, records in the CSV data can have different
+    /// lengths. By default, this is disabled, which will cause the CSV reader
+    /// to return an error if it tries to read a record that has a different
+    /// length than the first record that it read.
+    pub fn flexible(mut self, yes: bool) -> Reader<R> {
+        self.flexible = yes;
+        self
+    }
+
+    /// Whether the header row is exempt from the record length check.
     ///
-    /// When set to `None` (which is the default), the "doubling" escape
-    /// is used for quote character.
+    /// By default (when `flexible` is disabled), every record must have the
+    /// same length as the first row, which is normally the header row.
+    /// When this is enabled and `has_headers` is enabled, the header row
+    /// may have any length, and every record must have the same length as
+    /// the first *data* record instead. This is useful for data whose
+    /// header row has an extra (or a missing) label.
     ///
-    /// When set to something other than `None`, it is used as the escape
-    /// character for quotes. (e.g., `b'\\'`.)
-    pub fn escape(mut self, escape: Option<u8>) -> Reader<R> {
-        self.escape = escape;
+    /// This is disabled by default. It has no effect when `flexible` is
+    /// enabled.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "a,b,c,note\n1,2,3\n4,5,6";
+    /// let mut rdr = csv::Reader::from_string(data)
+    ///                           .header_excluded_from_length_check(true);
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![vec!["1", "2", "3"], vec!["4", "5", "6"]]);
+    /// ```
+    pub fn header_excluded_from_length_check(
+        mut self,
+        yes: bool,
+    ) -> Reader<R> {
+        self.header_excluded = yes;
         self
     }
 
-    /// Enable double quote escapes.
+    /// Set how the record iterators handle malformed records.
     ///
-    /// When disabled, doubled quotes are not interpreted as escapes.
-    pub fn double_quote(mut self, yes: bool) -> Reader<R> {
-        self.double_quote = yes;
+    /// This applies to `records`, `byte_records` and `decode` (and the
+    /// iterators built on top of them). The errors that can be recovered
+    /// from by `OnError::Skip` and `OnError::Lossy` are records with an
+    /// unexpected length and (for `String` records) invalid UTF-8. All
+    /// other errors, such as I/O errors, stop iteration under any policy.
+    ///
+    /// With `OnError::Abort`, iteration stops after the first error of any
+    /// kind, including invalid UTF-8 and decoding errors.
+    ///
+    /// By default, no policy is set. In that case, iteration stops after a
+    /// parse or I/O error, but continues after invalid UTF-8 or a decoding
+    /// error.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "a,b\n1,2\n3\n4,5";
+    /// let mut rdr = csv::Reader::from_string(data)
+    ///                           .on_error(csv::OnError::Skip);
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![vec!["1", "2"], vec!["4", "5"]]);
+    /// ```
+    pub fn on_error(mut self, policy: OnError) -> Reader<R> {
+        self.on_error = Some(policy);
         self
     }
 
-    /// A convenience method for reading ASCII delimited text.
+    /// Report recoverable errors to `sink` and continue with the next
+    /// record.
     ///
-    /// This sets the delimiter and record terminator to the ASCII unit
-    /// separator (`\x1f`) and record separator (`\x1e`), respectively.
+    /// This is for best effort reading with an error log. The record
+    /// iterators pass each record with an unexpected length and (for
+    /// `String` records) each record with invalid UTF-8 to `sink`, and then
+    /// skip it, as with `OnError::Skip`. All other errors, like I/O errors,
+    /// other parse errors and decoding errors, are still returned by the
+    /// iterators.
     ///
-    /// Since ASCII delimited text is meant to be unquoted, this also sets
-    /// `quote` to `None`.
-    pub fn ascii(self) -> Reader<R> {
-        self.delimiter(b'\x1f')
-            .record_terminator(RecordTerminator::Any(b'\x1e'))
+    /// This can be combined with `on_error`. With `OnError::Lossy`, invalid
+    /// UTF-8 is converted instead, so only unexpected lengths are reported.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let log = Arc::new(Mutex::new(vec![]));
+    /// let log2 = log.clone();
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2\n3\n4,5")
+    ///                           .error_sink(move |err| {
+    ///     log2.lock().unwrap().push(err.to_string());
+    /// });
+    /// let rows = rdr.collect_records().unwrap();
+    ///
+    /// assert_eq!(rows, vec![vec!["1", "2"], vec!["4", "5"]]);
+    /// assert_eq!(log.lock().unwrap().len(), 1);
+    /// ```
+    pub fn error_sink<F>(mut self, sink: F) -> Reader<R>
+            where F: FnMut(&Error) + Send + 'static {
+        self.error_sink = Some(Box::new(sink));
+        self
     }
-}
-
-/// NextField is the result of parsing a single CSV field.
-///
-/// This is only useful if you're using the low level `next_bytes` method.
-#[derive(Debug)]
-pub enum NextField<'a, T: ?Sized + 'a> {
-    /// A single CSV field as a borrowed slice of the parser's internal buffer.
-    Data(&'a T),
 
-    /// A CSV error found during parsing. When an error is found, it is
-    /// first returned. All subsequent calls of `next_bytes` will return
-    /// `EndOfCsv`. (EOF is exempt from this. Depending on the state of the
-    /// parser, an EOF could trigger `Data`, `EndOfRecord` and `EndOfCsv`,
-    /// all in succession.)
+    /// Limit the number of line terminators in a single quoted field.
     ///
-    /// In general, once `EndOfCsv` is returned, no other return value is
-    /// possible on subsequent calls.
-    Error(Error),
-
-    /// Indicates the end of a record.
-    EndOfRecord,
-
-    /// Indicates the end of the CSV data. Once this state is entered, the
-    /// parser can never leave it.
-    EndOfCsv,
-}
-
-impl<'a, T: ?Sized + ::std::fmt::Debug> NextField<'a, T> {
-    /// Transform NextField into an iterator result.
-    pub fn into_iter_result(self) -> Option<Result<&'a T>> {
-        match self {
-            NextField::EndOfRecord | NextField::EndOfCsv => None,
-            NextField::Error(err) => Some(Err(err)),
-            NextField::Data(field) => Some(Ok(field)),
-        }
+    /// When set, the reader returns a `ParseError::FieldTooManyLines` error
+    /// as soon as a quoted field contains more than `max` embedded record
+    /// terminators. (With the default `CRLF` terminator, `\r\n` counts as a
+    /// single terminator.) This guards against pathological input that
+    /// would otherwise cause a single field to grow without bound.
+    ///
+    /// This only applies inside quoted fields, since an unquoted record
+    /// terminator always ends the record.
+    ///
+    /// By default, there is no limit.
+    pub fn max_field_lines(mut self, max: Option<u64>) -> Reader<R> {
+        self.max_field_lines = max;
+        self
     }
 
-    /// Returns true if and only if the end of CSV data has been reached.
-    pub fn is_end(&self) -> bool {
-        if let NextField::EndOfCsv = *self { true } else { false }
+    /// Limit the number of fields in a single record.
+    ///
+    /// When set, the reader returns a `ParseError::TooManyFields` error as
+    /// soon as it parses field number `max + 1` of a record, before that
+    /// field is returned. This guards against pathological input (e.g., a
+    /// line of nothing but delimiters) that would otherwise cause a single
+    /// record to grow without bound. Unlike the `flexible` check, this
+    /// doesn't wait for the end of the record.
+    ///
+    /// This applies to every record, including the header row.
+    ///
+    /// By default, there is no limit.
+    pub fn max_fields(mut self, max: Option<u64>) -> Reader<R> {
+        self.max_fields = max;
+        self
     }
 
-    /// Returns the underlying field data.
+    /// Stop reading once `limit` bytes have been parsed.
     ///
-    /// If `NextField` is an error or an end of record/CSV marker, this will
-    /// panic.
-    pub fn unwrap(self) -> &'a T {
-        match self {
-            NextField::Data(field) => field,
-            v => panic!("Cannot unwrap '{:?}'", v),
-        }
+    /// When set, the reader reports the end of the CSV data at the first
+    /// record boundary at or after `limit` bytes. The record being parsed
+    /// when the limit is reached is always finished, so records are never
+    /// cut short. This is useful for previewing or sampling the start of a
+    /// huge file.
+    ///
+    /// The limit counts the bytes parsed (see `total_bytes_read`), so bytes
+    /// parsed again after seeking backwards count towards it again. It
+    /// starts counting anew after `seek_to_start`.
+    ///
+    /// By default, there is no limit.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "a,b\n1,2\n3,4\n5,6\n";
+    /// let mut rdr = csv::Reader::from_string(data).read_limit(Some(9));
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![vec!["1", "2"], vec!["3", "4"]]);
+    /// ```
+    pub fn read_limit(mut self, limit: Option<u64>) -> Reader<R> {
+        self.read_limit = limit;
+        self
     }
-}
 
-/// These are low level methods for dealing with the raw bytes of CSV records.
-/// You should only need to use these when you need the performance or if
-/// your CSV data isn't UTF-8 encoded.
-impl<R: io::Read> Reader<R> {
-    /// This is just like `headers`, except fields are `ByteString`s instead
-    /// of `String`s.
-    pub fn byte_headers(&mut self) -> Result<Vec<ByteString>> {
-        if !self.first_row.is_empty() {
-            Ok(self.first_row.clone())
-        } else {
-            let mut headers = vec![];
-            loop {
-                let field = match self.next_bytes() {
-                    NextField::EndOfRecord | NextField::EndOfCsv => break,
-                    NextField::Error(err) => return Err(err),
-                    NextField::Data(field) => field,
-                };
-                headers.push(field.to_vec());
-            }
-            assert!(headers.len() > 0 || self.done());
-            Ok(headers)
-        }
+    /// Set the record terminator to use when reading CSV data.
+    ///
+    /// In the vast majority of situations, you'll want to use the default
+    /// value, `RecordTerminator::CRLF`, which automatically handles `\r`,
+    /// `\n` or `\r\n` as record terminators. (Notably, this is a special
+    /// case since two characters can correspond to a single terminator token.)
+    ///
+    /// However, you may use `RecordTerminator::Any` to specify any ASCII
+    /// character to use as the record terminator. For example, you could
+    /// use `RecordTerminator::Any(b'\n')` to only accept line feeds as
+    /// record terminators, or `b'\x1e'` for the ASCII record separator.
+    ///
+    /// Finally, `RecordTerminator::None` reads all of the data as a single
+    /// record that is only split by the delimiter. This is for data that is
+    /// really one giant row, whose fields may contain line terminators
+    /// without being quoted.
+    pub fn record_terminator(mut self, term: RecordTerminator) -> Reader<R> {
+        self.record_term = term;
+        self
     }
 
-    /// This is just like `records`, except fields are `ByteString`s instead
-    /// of `String`s.
-    pub fn byte_records<'a>(&'a mut self) -> ByteRecords<'a, R> {
-        let first = self.has_seeked;
-        ByteRecords { p: self, first: first, errored: false }
+    /// Set the quote character to use when reading CSV data.
+    ///
+    /// Since the CSV reader is meant to be mostly encoding agnostic, you must
+    /// specify the quote as a single ASCII byte. For example, to read
+    /// single quoted data, you would use `b'\''`.
+    ///
+    /// The default value is `b'"'`.
+    ///
+    /// If `quote` is `None`, then no quoting will be used.
+    pub fn quote(mut self, quote: u8) -> Reader<R> {
+        self.quote = quote;
+        self.close_quote = quote;
+        self
     }
 
-    /// Returns `true` if the CSV parser has reached its final state. When
-    /// this method returns `true`, all iterators will always return `None`.
+    /// Set different characters for opening and closing quoted fields.
     ///
-    /// This is not needed in typical usage since the record iterators will
-    /// stop for you when the parser completes. This method is useful when
-    /// you're accessing the parser's lowest-level iterator.
+    /// Some formats use an asymmetric pair of quotes, like `[` and `]`. With
+    /// this set, `open` starts a quoted field and `close` ends it, so that
+    /// `[a,b]` is read as the single field `a,b`. An `open` character inside
+    /// a quoted field is just data.
     ///
-    /// ### Example
+    /// To embed the `close` character in a quoted field, double it (e.g.,
+    /// `[a]]b]` is read as `a]b`), or precede it with the `escape`
+    /// character if one is set. Doubling can be disabled with
+    /// `double_quote` as usual.
     ///
-    /// This is the *fastest* way to compute the number of records in CSV data
-    /// using this crate. (It is fast because it does not allocate space for
-    /// every field.)
+    /// Calling `quote` afterwards sets both characters back to the same
+    /// one.
+    ///
+    /// ### Example
     ///
     /// ```rust
-    /// let data = "
-    /// sticker,mortals,7
-    /// bribed,personae,7
-    /// wobbling,poncing,4
-    /// interposed,emmett,9
-    /// chocolate,refile,7";
+    /// let mut rdr = csv::Reader::from_string("[a,b],[c]]d]\n")
+    ///                           .has_headers(false)
+    ///                           .quote_pair(b'[', b']');
+    /// let rows = rdr.records().collect::<csv::Result<Vec<_>>>().unwrap();
+    /// assert_eq!(rows, vec![vec!["a,b", "c]d"]]);
+    /// ```
+    pub fn quote_pair(mut self, open: u8, close: u8) -> Reader<R> {
+        self.quote = open;
+        self.close_quote = close;
+        self
+    }
+
+    /// Set an alternate quote character to use when reading CSV data.
     ///
-    /// let mut rdr = csv::Reader::from_string(data);
-    /// let mut count = 0u64;
-    /// while !rdr.done() {
-    ///     loop {
-    ///         // This case analysis is necessary because we only want to
-    ///         // increment the count when `EndOfRecord` is seen. (If the
-    ///         // CSV data is empty, then it will never be emitted.)
-    ///         match rdr.next_bytes() {
-    ///             csv::NextField::EndOfCsv => break,
-    ///             csv::NextField::EndOfRecord => { count += 1; break; },
-    ///             csv::NextField::Error(err) => panic!(err),
-    ///             csv::NextField::Data(_) => {}
-    ///         }
-    ///     }
-    /// }
+    /// Some exports quote fields with either of two characters, e.g., `"`
+    /// or `'`. With this set, a field may be quoted with either `quote` or
+    /// `alt`, and it must be closed by the same character it was opened
+    /// with. The other character is just data inside the field, so
+    /// `'say "hi"'` is read as `say "hi"`.
     ///
-    /// assert_eq!(count, 5);
+    /// The closing character is escaped by doubling it (or with the
+    /// `escape` character, if one is set), so `'it''s'` is read as `it's`.
+    /// When `quote_pair` is used, a field opened with `alt` is also closed
+    /// with `alt`.
+    ///
+    /// By default, there is no alternate quote character.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("\"a,'b\",'c,\"d','e''f'")
+    ///                           .has_headers(false)
+    ///                           .alt_quote(Some(b'\''));
+    /// let rows = rdr.records().collect::<csv::Result<Vec<_>>>().unwrap();
+    /// assert_eq!(rows, vec![vec!["a,'b", "c,\"d", "e'f"]]);
     /// ```
-    pub fn done(&self) -> bool {
-        self.eof
+    pub fn alt_quote(mut self, alt: Option<u8>) -> Reader<R> {
+        self.alt_quote = alt;
+        self
     }
 
-    /// An iterator over fields in the current record.
+    /// Set the character that starts a comment line.
     ///
-    /// This provides low level access to CSV records as raw byte slices.
-    /// Namely, no allocation is performed. Unlike other iterators in this
-    /// crate, this yields *fields* instead of records. Notably, this cannot
-    /// implement the `Iterator` trait safely. As such, it cannot be used with
-    /// a `for` loop.
+    /// When set, a line whose first byte is `comment` is ignored entirely,
+    /// just like an empty line. This happens in the parser itself, so
+    /// comment lines never show up as records in any method of the reader,
+    /// e.g., `headers`, the record iterators, `index_every` and `stats`.
+    /// Their bytes still count towards byte offsets, though, so just like
+    /// empty lines, `stats` counts them as part of the record after them.
     ///
-    /// See the documentation for the `NextField` type on how the iterator
-    /// works.
+    /// Only the first byte of a line is checked, so a comment character
+    /// after leading whitespace or in the middle of a record is just data.
+    /// Comment lines are recognized before `leading_fixed` is applied, but
+    /// lines skipped by `skip_lines` are skipped first.
     ///
-    /// This iterator always returns all records (i.e., it won't skip the
-    /// header row).
+    /// By default, there are no comments.
     ///
     /// ### Example
     ///
-    /// This method is most useful when used in conjunction with the the
-    /// `done` method:
-    ///
     /// ```rust
-    /// let data = "
-    /// sticker,mortals,7
-    /// bribed,personae,7
-    /// wobbling,poncing,4
-    /// interposed,emmett,9
-    /// chocolate,refile,7";
+    /// let data = "# exported 2016-01-01\na,b\n# first\n1,2\n";
+    /// let mut rdr = csv::Reader::from_string(data).comment(Some(b'#'));
     ///
-    /// let mut rdr = csv::Reader::from_string(data);
-    /// while !rdr.done() {
-    ///     while let Some(r) = rdr.next_bytes().into_iter_result() {
-    ///         print!("{:?} ", r.unwrap());
-    ///     }
-    ///     println!("");
-    /// }
+    /// assert_eq!(rdr.headers().unwrap(), vec!["a", "b"]);
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![vec!["1", "2"]]);
     /// ```
-    pub fn next_bytes(&mut self) -> NextField<[u8]> {
-        unsafe { self.fieldbuf.set_len(0); }
-        loop {
-            if let Err(err) = self.fill_buf() {
-                return NextField::Error(Error::Io(err));
-            }
-            if self.buf.len() == 0 {
-                self.eof = true;
-                if let StartRecord = self.state {
-                    return self.next_eoc();
-                } else if let EndRecord = self.state {
-                    self.state = StartRecord;
-                    return self.next_eor();
-                } else {
-                    self.state = EndRecord;
-                    return self.next_data();
-                }
-            }
-            while self.bufi < self.buf.len() {
-                let c = self.buf[self.bufi];
-                match self.state {
-                    StartRecord => {
-                        if self.is_record_term(c) {
-                            self.bump();
+    pub fn comment(mut self, comment: Option<u8>) -> Reader<R> {
+        self.comment = comment;
+        self
+    }
+
+    /// Set the escape character to use when reading CSV data.
+    ///
+    /// Since the CSV reader is meant to be mostly encoding agnostic, you must
+    /// specify the escape as a single ASCII byte.
+    ///
+    /// When set to `None` (which is the default), the "doubling" escape
+    /// is used for quote character.
+    ///
+    /// When set to something other than `None`, it is used as the escape
+    /// character for quotes. (e.g., `b'\\'`.)
+    pub fn escape(mut self, escape: Option<u8>) -> Reader<R> {
+        self.escape = escape;
+        self
+    }
+
+    /// Whether an escape that isn't followed by a quote is an error.
+    ///
+    /// Inside a quoted field, the escape character (see `escape`) may only
+    /// be followed by the quote character or by another escape character.
+    /// By default, an escape followed by anything else is kept as a literal
+    /// byte. For example, with `escape(Some(b'\\'))`, the field
+    /// `"C:\\path"` is read as `C:\path`.
+    ///
+    /// When this is enabled, such an escape is reported as a
+    /// `ParseError::InvalidEscape` instead.
+    ///
+    /// This is disabled by default.
+    pub fn strict_escape(mut self, yes: bool) -> Reader<R> {
+        self.strict_escape = yes;
+        self
+    }
+
+    /// Set where the escape character is recognized.
+    ///
+    /// By default, the escape character is only recognized inside quoted
+    /// fields (`EscapeMode::QuotedOnly`). With `EscapeMode::Everywhere`, it
+    /// also escapes the byte following it in unquoted fields. For example,
+    /// with `escape(Some(b'\\'))`, the data `a\,b` is then read as the
+    /// single field `a,b`.
+    ///
+    /// Inside quoted fields, the escape character takes precedence over
+    /// `double_quote` (i.e., `\"` is always an escaped quote), but `""` is
+    /// still read as an escaped quote when `double_quote` is enabled.
+    ///
+    /// This has no effect unless an escape character is set.
+    pub fn escape_mode(mut self, mode: EscapeMode) -> Reader<R> {
+        self.escape_mode = mode;
+        self
+    }
+
+    /// Enable double quote escapes.
+    ///
+    /// When disabled, doubled quotes are not interpreted as escapes.
+    pub fn double_quote(mut self, yes: bool) -> Reader<R> {
+        self.double_quote = yes;
+        self
+    }
+
+    /// Checks that the delimiter, quote and escape bytes don't conflict with
+    /// the record terminator.
+    ///
+    /// None of these bytes may be `\r` or `\n` (which always end a line),
+    /// or the byte set with `RecordTerminator::Any`. Using one of them
+    /// doesn't fail when reading, but silently produces nonsensical records,
+    /// so it is worth calling this after configuring a reader from user
+    /// input.
+    ///
+    /// If there is a conflict, an `Error::Config` describing it is returned.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let rdr = csv::Reader::from_string("a\tb").delimiter(b'\t');
+    /// assert!(rdr.validate().is_ok());
+    ///
+    /// let rdr = csv::Reader::from_string("a\nb").quote(b'\n');
+    /// assert!(rdr.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        let mut bytes = vec![
+            ("delimiter", self.delimiter),
+            ("quote", self.quote),
+            ("quote", self.close_quote),
+        ];
+        if let Some(alt) = self.alt_quote {
+            bytes.push(("quote", alt));
+        }
+        if let Some(escape) = self.escape {
+            bytes.push(("escape", escape));
+        }
+        if let Some(comment) = self.comment {
+            bytes.push(("comment", comment));
+        }
+        for &(name, b) in &bytes {
+            if b == b'\r' || b == b'\n' || self.record_term == b {
+                return Err(Error::Config(format!(
+                    "{} {:?} is a record terminator",
+                    name, b as char)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether to keep quotes and escapes in field values.
+    ///
+    /// When enabled, fields are returned exactly as they appear in the CSV
+    /// data, including surrounding quotes and any doubled or escaped quotes
+    /// inside them. For example, the field `"a""b"` is returned as the six
+    /// bytes `"a""b"` instead of `a"b`. This is useful when the exact source
+    /// representation of a field is needed.
+    ///
+    /// Quoting is still used to find field and record boundaries, so a
+    /// delimiter or record terminator inside a quoted field does not end it.
+    ///
+    /// This is disabled by default.
+    pub fn preserve_quotes(mut self, yes: bool) -> Reader<R> {
+        self.preserve_quotes = yes;
+        self
+    }
+
+    /// Whether to keep stray quotes that follow a closing quote.
+    ///
+    /// A quote only starts a quoted field at the very beginning of a field.
+    /// Any other quote that appears in an unquoted field, like in
+    /// `foo"bar"baz`, is always read as a literal byte.
+    ///
+    /// By default, a quoted field that continues after its closing quote,
+    /// like `"quoted"trailing`, has its closing quote dropped (giving
+    /// `quotedtrailing`). When this is enabled, the closing quote is kept
+    /// as a literal byte instead (giving `quoted"trailing`), which is
+    /// usually what the author of such data intended.
+    ///
+    /// This is disabled by default.
+    pub fn relaxed_quotes(mut self, yes: bool) -> Reader<R> {
+        self.relaxed_quotes = yes;
+        self
+    }
+
+    /// Whether to keep the record that was parsed last.
+    ///
+    /// When enabled, the fields of each record are kept as they are parsed,
+    /// so that the last record is available from `last_byte_record` and
+    /// `last_record`, and its bytes as they appear in the CSV data from
+    /// `last_raw_bytes`. This includes a record that caused a
+    /// `ParseError::UnequalLengths` error, which makes it possible to
+    /// report the actual bad row. The fields are as parsed, i.e., after
+    /// quotes have been removed and any trimming has been applied.
+    ///
+    /// This is disabled by default, since it copies every field.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2,3\n")
+    ///                           .capture_raw(true);
+    /// assert!(rdr.records().next().unwrap().is_err());
+    /// let record = rdr.last_byte_record().unwrap();
+    /// assert_eq!(record, &[b"1".to_vec(), b"2".to_vec(), b"3".to_vec()]);
+    /// assert_eq!(rdr.last_raw_bytes(), Some(&b"1,2,3\n"[..]));
+    /// ```
+    pub fn capture_raw(mut self, yes: bool) -> Reader<R> {
+        self.capture_raw = yes;
+        self
+    }
+
+    /// Returns the record that was parsed last.
+    ///
+    /// This is only available when `capture_raw` is enabled, so that the
+    /// fields of every record are kept. Otherwise, or if no record has been
+    /// parsed in full yet, `None` is returned. It is also `None` while a
+    /// record is only partially read with `next_bytes`, after a record
+    /// failed to parse and after seeking. The exception is a record that
+    /// has a different length than the others: it is parsed in full before
+    /// the `UnequalLengths` error is returned, so it is available here.
+    ///
+    /// Note that the record that was parsed last is the one that was peeked
+    /// at, if any (e.g., with `peek_field_count`).
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2\n")
+    ///                           .capture_raw(true);
+    /// let row = rdr.byte_records().next().unwrap().unwrap();
+    /// assert_eq!(rdr.last_byte_record(), Some(&row[..]));
+    /// ```
+    pub fn last_byte_record(&self) -> Option<&[ByteString]> {
+        if self.raw_done {
+            Some(&self.raw_record)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the bytes of the record that was parsed last, exactly as
+    /// they appear in the CSV data.
+    ///
+    /// This includes all quotes, escapes and whitespace, along with the
+    /// record terminator (if any). It is available under the same
+    /// conditions as `last_byte_record`, i.e., when `capture_raw` is
+    /// enabled. Empty lines and comments between records don't belong to
+    /// any record, so they are not included.
+    ///
+    /// Writing these bytes with `Writer::write_raw` reproduces the record
+    /// byte-for-byte, which is useful for editing some records of a file
+    /// while leaving all others untouched.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("\"a\"\"b\",c\r\nd,e")
+    ///                           .has_headers(false)
+    ///                           .capture_raw(true);
+    /// rdr.byte_records().next().unwrap().unwrap();
+    /// assert_eq!(rdr.last_raw_bytes(), Some(&b"\"a\"\"b\",c\r\n"[..]));
+    /// ```
+    pub fn last_raw_bytes(&self) -> Option<&[u8]> {
+        if self.raw_done {
+            Some(&self.raw_bytes)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a copy of the record that was parsed last as strings.
+    ///
+    /// This is just like `last_byte_record`, except that the fields are
+    /// converted to `String`s. If a field isn't valid UTF-8, then an error
+    /// is returned.
+    pub fn last_record(&self) -> Option<Result<Vec<String>>> {
+        self.last_byte_record().map(|r| byte_record_to_utf8(r.to_vec()))
+    }
+
+    /// Whether content after the closing quote of a field is an error.
+    ///
+    /// A quoted field should end right after its closing quote, i.e., the
+    /// closing quote should be followed by a delimiter or a record
+    /// terminator. When this is enabled, data like `"ab"x` is reported as a
+    /// `ParseError::TrailingQuoteContent` instead of being read as a field
+    /// (see `relaxed_quotes`). A doubled quote, like in `"ab""cd"`, is
+    /// still accepted as long as `double_quote` is enabled.
+    ///
+    /// This is disabled by default.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("\"ab\"x,y")
+    ///                           .has_headers(false)
+    ///                           .error_trailing_quote_content(true);
+    /// assert!(rdr.records().next().unwrap().is_err());
+    /// ```
+    pub fn error_trailing_quote_content(mut self, yes: bool) -> Reader<R> {
+        self.trailing_quote_error = yes;
+        self
+    }
+
+    /// Whether to keep track of which fields are quoted.
+    ///
+    /// When enabled, the reader records whether each field of a record
+    /// starts with a quote, which is available from `last_record_quoted`.
+    ///
+    /// This is disabled by default.
+    pub fn track_quotes(mut self, yes: bool) -> Reader<R> {
+        self.track_quotes = yes;
+        self
+    }
+
+    /// A convenience method for reading ASCII delimited text.
+    ///
+    /// This sets the delimiter and record terminator to the ASCII unit
+    /// separator (`\x1f`) and record separator (`\x1e`), respectively.
+    ///
+    /// Since ASCII delimited text is meant to be unquoted, this also sets
+    /// `quote` to `None`.
+    pub fn ascii(self) -> Reader<R> {
+        self.delimiter(b'\x1f')
+            .record_terminator(RecordTerminator::Any(b'\x1e'))
+    }
+
+    /// Trim the given bytes from the start and end of every field.
+    ///
+    /// This is useful for data that pads its fields with spaces or some
+    /// other filler byte. For example, `trim_chars(b" *")` turns the field
+    /// `** a b **` into `a b`. Only bytes at the edges of a field are
+    /// removed.
+    ///
+    /// Trimming is applied to the final value of a field, i.e., after any
+    /// quotes have been removed. It applies to every record, including the
+    /// header row.
+    ///
+    /// By default, no bytes are trimmed. Passing an empty set disables
+    /// trimming.
+    pub fn trim_chars(mut self, chars: &[u8]) -> Reader<R> {
+        self.trim_chars = chars.to_vec();
+        self
+    }
+
+    /// Treat fields equal to `marker` as missing values when decoding.
+    ///
+    /// Databases often export `NULL` as a literal marker such as `\N` or
+    /// `NULL`. With this set, `decode` decodes a field equal to `marker` as
+    /// `None` into an `Option` field, and returns a decoding error if the
+    /// field's type isn't an `Option`.
+    ///
+    /// This only applies to `decode`. Methods that return raw records,
+    /// like `records` and `byte_records`, leave the field as is.
+    ///
+    /// By default, no null marker is set. (Empty fields are always decoded
+    /// as `None` into an `Option` field.)
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,\\N\n")
+    ///                           .null_value("\\N");
+    /// let rows = rdr.decode().collect::<csv::Result<Vec<_>>>().unwrap();
+    /// let rows: Vec<(i32, Option<i32>)> = rows;
+    /// assert_eq!(rows, vec![(1, None)]);
+    /// ```
+    pub fn null_value(mut self, marker: &str) -> Reader<R> {
+        self.null_value = Some(marker.as_bytes().to_vec());
+        self
+    }
+
+    /// Whether to skip lines that contain only whitespace.
+    ///
+    /// Empty lines are always skipped. When this is enabled, a record that
+    /// consists of a single unquoted field made up entirely of ASCII
+    /// whitespace (spaces, tabs, form feeds or vertical tabs) is skipped as
+    /// well, just like an empty line. This is useful for exports that end
+    /// with a line of spaces, which would otherwise be read as a spurious
+    /// record.
+    ///
+    /// A quoted field is always kept, so `"  "` on a line of its own is
+    /// still read as a record.
+    ///
+    /// This is disabled by default.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "a,b\n1,2\n   \n";
+    /// let mut rdr = csv::Reader::from_string(data)
+    ///                           .ignore_whitespace_only_lines(true);
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![vec!["1", "2"]]);
+    /// ```
+    pub fn ignore_whitespace_only_lines(mut self, yes: bool) -> Reader<R> {
+        self.ignore_blank_lines = yes;
+        self
+    }
+
+    /// Set the width of a fixed-width prefix at the start of every record.
+    ///
+    /// Some formats start each line with a fixed-width segment (e.g., a
+    /// record type or length) that is followed by CSV fields. When `width`
+    /// is greater than `0`, the first `width` bytes of every record
+    /// (including the header row) are set aside before the rest of the
+    /// record is parsed. These bytes are never treated as delimiters,
+    /// quotes or escapes. They are available from `last_leading`.
+    ///
+    /// If a record terminator appears within the prefix, then the prefix
+    /// is cut short and the record has a single empty field.
+    ///
+    /// The default width is `0`, i.e., no prefix.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "0001a,b\n0002c,d\n";
+    /// let mut rdr = csv::Reader::from_string(data)
+    ///                           .has_headers(false)
+    ///                           .leading_fixed(4);
+    /// let mut rows = vec![];
+    /// while let Some(row) = rdr.records().next() {
+    ///     let lead = String::from_utf8(rdr.last_leading().to_vec()).unwrap();
+    ///     rows.push((lead, row.unwrap()));
+    /// }
+    /// assert_eq!(rows, vec![
+    ///     ("0001".to_string(), vec!["a".to_string(), "b".to_string()]),
+    ///     ("0002".to_string(), vec!["c".to_string(), "d".to_string()]),
+    /// ]);
+    /// ```
+    pub fn leading_fixed(mut self, width: usize) -> Reader<R> {
+        self.leading_width = width;
+        self
+    }
+
+    /// Select and reorder the columns of every record.
+    ///
+    /// Each record returned by the record iterators (`decode`, `records`,
+    /// `byte_records`, etc.) then contains exactly the columns at the
+    /// indices given (starting at `0`), in the order given. The same applies
+    /// to `headers` and `byte_headers`, so headers stay lined up with their
+    /// columns. A column may be selected more than once.
+    ///
+    /// By default, selecting a column that a record doesn't have produces
+    /// an empty field. Use `project_strict` to report an error instead.
+    ///
+    /// Records are projected after they are parsed, so options like
+    /// `flexible` still apply to the full records. The low level
+    /// `next_bytes` and `records_arena` methods (and `peek_headers`) are not
+    /// affected.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,b,c\n1,2,3")
+    ///                           .project(vec![2, 0]);
+    ///
+    /// assert_eq!(rdr.headers().unwrap(), vec!["c", "a"]);
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![vec!["3", "1"]]);
+    /// ```
+    pub fn project(mut self, cols: Vec<usize>) -> Reader<R> {
+        self.projection = Some(cols);
+        self
+    }
+
+    /// Append a column to every record.
+    ///
+    /// This adds provenance or other data to the records without having to
+    /// rebuild them, e.g., the name of the file they came from or their
+    /// record number. The column is added at the end of every record, after
+    /// any columns selected with `project`. If this is called more than
+    /// once, then the columns are added in the order of the calls.
+    ///
+    /// The header row returned by `headers` and `byte_headers` gets `name`
+    /// as the column's header if `has_headers` is enabled. Otherwise, the
+    /// first row is a record like any other, so it gets a value instead.
+    ///
+    /// Like `project`, this applies to the record iterators and headers,
+    /// but not to `next_bytes`, `records_arena` and `peek_headers`. Added
+    /// columns don't count towards the `flexible` and `max_fields` checks,
+    /// which only see the fields in the CSV data.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use csv::ColumnSource;
+    ///
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2\n3,4\n")
+    ///     .add_column("file", ColumnSource::Value("data.csv".to_string()))
+    ///     .add_column("record", ColumnSource::RecordNumber);
+    ///
+    /// assert_eq!(rdr.headers().unwrap(), vec!["a", "b", "file", "record"]);
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![
+    ///     vec!["1", "2", "data.csv", "2"],
+    ///     vec!["3", "4", "data.csv", "3"],
+    /// ]);
+    /// ```
+    pub fn add_column(mut self, name: &str, value: ColumnSource) -> Reader<R> {
+        self.added.push((name.as_bytes().to_vec(), value));
+        self
+    }
+
+    /// Whether selecting a missing column with `project` is an error.
+    ///
+    /// When enabled, a `ParseError::MissingColumn` error is returned for any
+    /// record that doesn't have one of the projected columns. When disabled
+    /// (the default), an empty field is used instead.
+    pub fn project_strict(mut self, yes: bool) -> Reader<R> {
+        self.projection_strict = yes;
+        self
+    }
+
+    /// Returns true if this reader allows records of different lengths.
+    ///
+    /// This is set with `flexible`.
+    pub fn is_flexible(&self) -> bool {
+        self.flexible
+    }
+
+    /// Returns the capacity of this reader's internal buffer, in bytes.
+    ///
+    /// This is the most data the reader requests from the underlying
+    /// `io::Read` at a time.
+    pub fn buffer_capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+}
+
+/// Statistics about the records in CSV data, as returned by
+/// `Reader::stats`.
+///
+/// All sizes are in bytes. When there are no records, every field is `0`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReaderStats {
+    /// The number of records read.
+    pub records: u64,
+    /// The number of bytes read.
+    pub total_bytes: u64,
+    /// The size of the smallest record.
+    pub min_record_bytes: u64,
+    /// The size of the largest record.
+    pub max_record_bytes: u64,
+    /// The mean size of a record.
+    pub mean_record_bytes: f64,
+    /// The largest number of fields in a record.
+    pub max_fields: u64,
+}
+
+/// The inferred type of a column, as returned by `Reader::infer_schema`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnType {
+    /// The column's header, if the CSV data has headers.
+    pub name: Option<String>,
+    /// The kind of values in the column.
+    pub kind: ColumnKind,
+    /// Whether the column has empty fields.
+    pub nullable: bool,
+}
+
+/// The kind of values in a column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnKind {
+    /// Integers that fit in an `i64`.
+    Int,
+    /// Floating point numbers (and integers).
+    Float,
+    /// The booleans `true` and `false`.
+    Bool,
+    /// Anything else.
+    Text,
+}
+
+impl ColumnKind {
+    /// Returns the narrowest kind of value that `field` can be parsed as.
+    fn of(field: &[u8]) -> ColumnKind {
+        let s = match str::from_utf8(field) {
+            Err(_) => return ColumnKind::Text,
+            Ok(s) => s.trim(),
+        };
+        if s.parse::<i64>().is_ok() {
+            ColumnKind::Int
+        } else if is_numeric(s.as_bytes()) {
+            ColumnKind::Float
+        } else if s.parse::<bool>().is_ok() {
+            ColumnKind::Bool
+        } else {
+            ColumnKind::Text
+        }
+    }
+
+    /// Returns the narrowest kind that covers both `self` and `other`.
+    fn merge(self, other: ColumnKind) -> ColumnKind {
+        use self::ColumnKind::*;
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Int, Float) | (Float, Int) => Float,
+            _ => Text,
+        }
+    }
+}
+
+/// The result of reading a record with `Reader::read_record_into`.
+///
+/// The counts in each variant are the number of field ends and bytes written
+/// to the buffers given to the call that produced it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadInto {
+    /// A complete record (or the rest of one) was read.
+    Record {
+        /// The number of field ends written.
+        fields: usize,
+        /// The number of field bytes written.
+        bytes: usize,
+    },
+    /// The field buffer is full. The record is not finished.
+    OutputFull {
+        /// The number of field ends written.
+        fields: usize,
+        /// The number of field bytes written.
+        bytes: usize,
+    },
+    /// The field ends buffer is full. The record is not finished.
+    OutputEndsFull {
+        /// The number of field ends written.
+        fields: usize,
+        /// The number of field bytes written.
+        bytes: usize,
+    },
+    /// There are no more records.
+    End,
+}
+
+/// NextField is the result of parsing a single CSV field.
+///
+/// This is only useful if you're using the low level `next_bytes` method.
+#[derive(Debug)]
+pub enum NextField<'a, T: ?Sized + 'a> {
+    /// A single CSV field as a borrowed slice of the parser's internal buffer.
+    Data(&'a T),
+
+    /// A CSV error found during parsing. When an error is found, it is
+    /// first returned. All subsequent calls of `next_bytes` will return
+    /// `EndOfCsv`. (EOF is exempt from this. Depending on the state of the
+    /// parser, an EOF could trigger `Data`, `EndOfRecord` and `EndOfCsv`,
+    /// all in succession.)
+    ///
+    /// In general, once `EndOfCsv` is returned, no other return value is
+    /// possible on subsequent calls.
+    Error(Error),
+
+    /// Indicates the end of a record.
+    EndOfRecord,
+
+    /// Indicates the end of the CSV data. Once this state is entered, the
+    /// parser can never leave it.
+    EndOfCsv,
+}
+
+impl<'a, T: ?Sized + ::std::fmt::Debug> NextField<'a, T> {
+    /// Transform NextField into an iterator result.
+    pub fn into_iter_result(self) -> Option<Result<&'a T>> {
+        match self {
+            NextField::EndOfRecord | NextField::EndOfCsv => None,
+            NextField::Error(err) => Some(Err(err)),
+            NextField::Data(field) => Some(Ok(field)),
+        }
+    }
+
+    /// Returns true if and only if the end of CSV data has been reached.
+    pub fn is_end(&self) -> bool {
+        if let NextField::EndOfCsv = *self { true } else { false }
+    }
+
+    /// Returns the underlying field data.
+    ///
+    /// If `NextField` is an error or an end of record/CSV marker, this will
+    /// panic.
+    pub fn unwrap(self) -> &'a T {
+        match self {
+            NextField::Data(field) => field,
+            v => panic!("Cannot unwrap '{:?}'", v),
+        }
+    }
+}
+
+/// These are low level methods for dealing with the raw bytes of CSV records.
+/// You should only need to use these when you need the performance or if
+/// your CSV data isn't UTF-8 encoded.
+impl<R: io::Read> Reader<R> {
+    /// This is just like `headers`, except fields are `ByteString`s instead
+    /// of `String`s.
+    pub fn byte_headers(&mut self) -> Result<Vec<ByteString>> {
+        let parsed = self.first_row.is_empty();
+        let headers = if !parsed {
+            self.first_row.clone()
+        } else {
+            let mut headers = vec![];
+            loop {
+                let field = match self.next_bytes() {
+                    NextField::EndOfRecord | NextField::EndOfCsv => break,
+                    NextField::Error(err) => return Err(err),
+                    NextField::Data(field) => field,
+                };
+                headers.push(field.to_vec());
+            }
+            assert!(headers.len() > 0 || self.done());
+            headers
+        };
+        let mut headers = try!(self.project_record(headers, 1));
+        if !headers.is_empty() && !self.added.is_empty() {
+            if self.has_headers {
+                headers.extend(self.added.iter().map(|a| a.0.clone()));
+            } else {
+                let offset = self.first_row_start;
+                self.add_columns(&mut headers, 1, offset);
+            }
+        }
+        if parsed {
+            self.shape_quoted();
+        }
+        Ok(headers)
+    }
+
+    /// Returns the first row of the CSV data without copying it.
+    ///
+    /// This is just like `byte_headers`, except the first row is borrowed
+    /// from the reader and is returned as it was parsed, i.e., `project` and
+    /// `add_column` are not applied to it. Like `headers` and
+    /// `byte_headers`, this never affects the record iterators: when
+    /// `has_headers` is disabled, the first row is still returned as the
+    /// first record. This holds no matter whether this is called before,
+    /// during or after iterating over records.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2").has_headers(false);
+    /// let first = vec![b"a".to_vec(), b"b".to_vec()];
+    /// assert_eq!(rdr.peek_headers().unwrap(), &first[..]);
+    ///
+    /// let rows = rdr.byte_records().collect::<csv::Result<Vec<_>>>();
+    /// let rows = rows.unwrap();
+    /// assert_eq!(rows.len(), 2);
+    /// assert_eq!(rows[0], first);
+    /// ```
+    pub fn peek_headers(&mut self) -> Result<&[ByteString]> {
+        if self.first_row.is_empty() {
+            try!(self.byte_headers());
+        }
+        Ok(&self.first_row)
+    }
+
+    /// This is just like `records`, except fields are `ByteString`s instead
+    /// of `String`s.
+    pub fn byte_records<'a>(&'a mut self) -> ByteRecords<'a, R> {
+        ByteRecords { p: self, errored: false }
+    }
+
+    /// Returns an iterator of records whose fields are only checked to be
+    /// UTF-8 when they are accessed.
+    ///
+    /// The `records` iterator validates every field of every record, even
+    /// fields that are never used. The records yielded by this iterator
+    /// keep their fields as bytes instead, and each field is validated the
+    /// first time it is accessed with `LazyStringRecord::get`. This saves
+    /// time when only some of the columns are needed.
+    ///
+    /// The tradeoff is that invalid UTF-8 is reported when a field is
+    /// accessed rather than when the record is read, and invalid UTF-8 in
+    /// fields that are never accessed isn't reported at all.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = &b"a,\xff\nb,\xff"[..];
+    /// let mut rdr = csv::Reader::from_bytes(data).has_headers(false);
+    /// for row in rdr.lazy_records() {
+    ///     let row = row.unwrap();
+    ///     assert!(row.get(0).unwrap().is_ok());
+    ///     assert!(row.get(1).unwrap().is_err());
+    /// }
+    /// ```
+    pub fn lazy_records<'a>(&'a mut self) -> LazyRecords<'a, R> {
+        LazyRecords { p: self.byte_records() }
+    }
+
+    /// Returns the first field of the next record without consuming it.
+    ///
+    /// The next record is the one that would be returned next by any of the
+    /// record iterators (`decode`, `records`, `byte_records`, etc.), which
+    /// means the header row is skipped if `has_headers` is enabled. The
+    /// record is parsed in full and cached, so the next record iterator
+    /// still returns the complete record. Calling this method repeatedly
+    /// without reading a record returns the same field every time.
+    ///
+    /// `None` is returned if there are no more records.
+    ///
+    /// This is useful for routing records based on a leading tag column.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("kind,value\nA,1\nB,2");
+    ///
+    /// assert_eq!(rdr.peek_first_field().unwrap(), Some(&b"A"[..]));
+    /// let row = rdr.records().next().unwrap().unwrap();
+    /// assert_eq!(row, vec!["A", "1"]);
+    /// assert_eq!(rdr.peek_first_field().unwrap(), Some(&b"B"[..]));
+    /// ```
+    pub fn peek_first_field(&mut self) -> Result<Option<&[u8]>> {
+        let record = try!(self.peek_byte_record());
+        Ok(record.and_then(|r| r.first()).map(|f| &**f))
+    }
+
+    /// Pushes a record back so that the record iterators return it next.
+    ///
+    /// This is useful when a record has been read too far ahead, e.g., by a
+    /// parser that only knows it is done after seeing the first record that
+    /// doesn't belong to it. The record doesn't have to be the one that was
+    /// read last, but it usually is.
+    ///
+    /// Only one record can be pushed back at a time, and it shares its slot
+    /// with a record that was peeked at (e.g., with `peek_field_count`). If
+    /// the slot is taken, then nothing changes and `record` is returned as
+    /// an error. The slot is cleared by `seek`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a\n1\n2\n");
+    ///
+    /// let row = rdr.records().next().unwrap().unwrap();
+    /// assert_eq!(row, vec!["1"]);
+    /// rdr.unread_record(row).unwrap();
+    /// assert!(rdr.unread_record(vec!["x".to_string()]).is_err());
+    ///
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![vec!["1"], vec!["2"]]);
+    /// ```
+    pub fn unread_record(
+        &mut self,
+        record: Vec<String>,
+    ) -> result::Result<(), Vec<String>> {
+        if self.peeked.is_some() {
+            return Err(record);
+        }
+        self.put_back(record);
+        self.unshaped = None;
+        Ok(())
+    }
+
+    /// Pushes a record of byte strings back so that the record iterators
+    /// return it next.
+    ///
+    /// This is just like `unread_record`, but for records read with
+    /// `byte_records`.
+    pub fn unread_byte_record(
+        &mut self,
+        record: Vec<ByteString>,
+    ) -> result::Result<(), Vec<ByteString>> {
+        if self.peeked.is_some() {
+            return Err(record);
+        }
+        self.peeked = Some(record);
+        self.unshaped = None;
+        Ok(())
+    }
+
+    /// Returns the number of fields in the next record without consuming it.
+    ///
+    /// Like `peek_first_field`, the next record is parsed in full and
+    /// cached, so the next record iterator still returns it. Quoting is
+    /// honored, so a quoted delimiter does not start a new field.
+    ///
+    /// Since the record has been parsed, `byte_offset` reports the offset
+    /// just past the peeked record (i.e., the start of the record after it)
+    /// until it is read.
+    ///
+    /// `None` is returned if there are no more records.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,\"b,c\",d\n1,2,3")
+    ///                           .has_headers(false);
+    ///
+    /// assert_eq!(rdr.peek_field_count().unwrap(), Some(3));
+    /// let row = rdr.records().next().unwrap().unwrap();
+    /// assert_eq!(row, vec!["a", "b,c", "d"]);
+    /// ```
+    pub fn peek_field_count(&mut self) -> Result<Option<u64>> {
+        let record = try!(self.peek_byte_record());
+        Ok(record.map(|r| r.len() as u64))
+    }
+
+    /// Returns a reader of `String` records that are read in batches into a
+    /// single shared buffer.
+    ///
+    /// Unlike `records`, which allocates a `Vec<String>` for every record,
+    /// this reads up to `batch_size` records at a time into one buffer that
+    /// is reused for every batch. Records are handed out as borrowed views
+    /// into that buffer, so no allocation is performed once the buffer has
+    /// grown large enough to hold a batch.
+    ///
+    /// This is intended for read-and-discard workloads (e.g., computing
+    /// aggregates). Since the buffer is reset at the start of every batch,
+    /// the records in a batch cannot outlive the call to `next_batch` that
+    /// produced them. (The borrow checker enforces this.) If you need to keep
+    /// a record around, copy its fields out.
+    ///
+    /// Headers are handled exactly as they are with `records`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "
+    /// sticker,mortals,7
+    /// bribed,personae,7
+    /// wobbling,poncing,4
+    /// interposed,emmett,9
+    /// chocolate,refile,7";
+    ///
+    /// let mut rdr = csv::Reader::from_string(data).has_headers(false);
+    /// let mut batches = rdr.records_arena(2);
+    /// let mut total = 0;
+    /// while let Some(batch) = batches.next_batch() {
+    ///     let batch = batch.unwrap();
+    ///     for record in batch.iter() {
+    ///         total += record.get(2).unwrap().parse::<u32>().unwrap();
+    ///     }
+    /// }
+    /// assert_eq!(total, 34);
+    /// ```
+    pub fn records_arena<'a>(
+        &'a mut self,
+        batch_size: usize,
+    ) -> ArenaRecords<'a, R> {
+        ArenaRecords {
+            p: self,
+            errored: false,
+            err: None,
+            batch_size: ::std::cmp::max(1, batch_size),
+            buf: Vec::with_capacity(1024),
+            field_ends: vec![],
+            record_ends: vec![],
+        }
+    }
+
+    /// Returns `true` if the CSV parser has reached its final state. When
+    /// this method returns `true`, all iterators will always return `None`.
+    ///
+    /// This is not needed in typical usage since the record iterators will
+    /// stop for you when the parser completes. This method is useful when
+    /// you're accessing the parser's lowest-level iterator.
+    ///
+    /// ### Example
+    ///
+    /// This is the *fastest* way to compute the number of records in CSV data
+    /// using this crate. (It is fast because it does not allocate space for
+    /// every field.)
+    ///
+    /// ```rust
+    /// let data = "
+    /// sticker,mortals,7
+    /// bribed,personae,7
+    /// wobbling,poncing,4
+    /// interposed,emmett,9
+    /// chocolate,refile,7";
+    ///
+    /// let mut rdr = csv::Reader::from_string(data);
+    /// let mut count = 0u64;
+    /// while !rdr.done() {
+    ///     loop {
+    ///         // This case analysis is necessary because we only want to
+    ///         // increment the count when `EndOfRecord` is seen. (If the
+    ///         // CSV data is empty, then it will never be emitted.)
+    ///         match rdr.next_bytes() {
+    ///             csv::NextField::EndOfCsv => break,
+    ///             csv::NextField::EndOfRecord => { count += 1; break; },
+    ///             csv::NextField::Error(err) => panic!(err),
+    ///             csv::NextField::Data(_) => {}
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(count, 5);
+    /// ```
+    pub fn done(&self) -> bool {
+        self.eof
+    }
+
+    /// Checks that nothing but whitespace is left in the CSV data.
+    ///
+    /// This is meant to be called after reading all expected records, to
+    /// make sure that the data doesn't continue (e.g., because two files
+    /// were concatenated). The rest of the data is consumed as long as it
+    /// consists only of whitespace, i.e., spaces, tabs, form feeds,
+    /// vertical tabs and record terminators (including `\r` and `\n` with
+    /// the default `CRLF` terminator). Comment lines (see `comment`) are
+    /// skipped too.
+    ///
+    /// If anything else is found, then a `ParseError::TrailingData` error
+    /// with its byte offset is returned, and the line containing it can
+    /// still be read. This is also returned if a record has been read
+    /// ahead (e.g., with `peek_field_count`) or only partially read with
+    /// `next_bytes`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2\n \n\n");
+    /// assert_eq!(rdr.records().count(), 2);
+    /// assert!(rdr.assert_eof().is_ok());
+    ///
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2\n");
+    /// assert_eq!(rdr.records().take(1).count(), 1);
+    /// assert!(rdr.assert_eof().is_ok());
+    ///
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2\n3,4\n");
+    /// assert_eq!(rdr.records().take(1).count(), 1);
+    /// assert!(rdr.assert_eof().is_err());
+    /// ```
+    pub fn assert_eof(&mut self) -> Result<()> {
+        let boundary = match self.state {
+            StartRecord | EndRecord => self.peeked.is_none(),
+            _ => false,
+        };
+        if !boundary {
+            return Err(self.trailing_data(self.byte_offset));
+        }
+        let (mut line_start, mut in_comment) = (true, false);
+        loop {
+            if let Err(err) = self.fill_buf() {
+                // As in `next_bytes`, this is just the end of the data.
+                if err.kind() != io::ErrorKind::UnexpectedEof {
+                    return Err(Error::Io(err));
+                }
+            }
+            if self.buf.is_empty() {
+                if let StartRecord = self.state {
+                    self.eof = true;
+                }
+                return Ok(());
+            }
+            // Whitespace and comments are only consumed a whole line at a
+            // time, so that trailing data is left intact for the caller to
+            // read.
+            let mut i = self.bufi;
+            while i < self.buf.len() {
+                let c = self.buf[i];
+                if self.is_record_term(c) {
+                    while self.bufi <= i {
+                        self.bump();
+                    }
+                    line_start = true;
+                    in_comment = false;
+                } else if in_comment
+                        || (line_start && self.comment == Some(c)) {
+                    in_comment = true;
+                } else if is_blank(c) {
+                    line_start = false;
+                } else {
+                    let pos = self.byte_offset + (i - self.bufi) as u64;
+                    return Err(self.trailing_data(pos));
+                }
+                i += 1;
+            }
+            while self.bufi < self.buf.len() {
+                self.bump();
+            }
+        }
+    }
+
+    fn trailing_data(&self, pos: u64) -> Error {
+        Error::Parse(LocatableError {
+            record: self.irecord,
+            field: self.ifield,
+            err: ParseError::TrailingData { pos: pos },
+        })
+    }
+
+    /// An iterator over fields in the current record.
+    ///
+    /// This provides low level access to CSV records as raw byte slices.
+    /// Namely, no allocation is performed. Unlike other iterators in this
+    /// crate, this yields *fields* instead of records. Notably, this cannot
+    /// implement the `Iterator` trait safely. As such, it cannot be used with
+    /// a `for` loop.
+    ///
+    /// See the documentation for the `NextField` type on how the iterator
+    /// works.
+    ///
+    /// This iterator always returns all records (i.e., it won't skip the
+    /// header row).
+    ///
+    /// ### Example
+    ///
+    /// This method is most useful when used in conjunction with the the
+    /// `done` method:
+    ///
+    /// ```rust
+    /// let data = "
+    /// sticker,mortals,7
+    /// bribed,personae,7
+    /// wobbling,poncing,4
+    /// interposed,emmett,9
+    /// chocolate,refile,7";
+    ///
+    /// let mut rdr = csv::Reader::from_string(data);
+    /// while !rdr.done() {
+    ///     while let Some(r) = rdr.next_bytes().into_iter_result() {
+    ///         print!("{:?} ", r.unwrap());
+    ///     }
+    ///     println!("");
+    /// }
+    /// ```
+    pub fn next_bytes(&mut self) -> NextField<[u8]> {
+        unsafe { self.fieldbuf.set_len(0); }
+        loop {
+            if let StartRecord = self.state {
+                if self.at_read_limit() {
+                    self.eof = true;
+                    return self.next_eoc();
+                }
+            }
+            if self.capture_raw && self.bufi == self.buf.len() {
+                // The buffer is about to be refilled.
+                self.save_raw_bytes();
+                self.raw_from = 0;
+            }
+            if let Err(err) = self.fill_buf() {
+                // Some readers report running out of data as an error.
+                // That's only a problem if it happens in the middle of a
+                // record. Otherwise, treat it just like a normal EOF.
+                match self.state {
+                    StartRecord | EndRecord | InComment
+                        if err.kind() == io::ErrorKind::UnexpectedEof => {}
+                    _ => return NextField::Error(Error::Io(err)),
+                }
+            }
+            if self.buf.len() == 0 {
+                self.eof = true;
+                self.cr_pending = false;
+                if let InComment = self.state {
+                    self.state = StartRecord;
+                }
+                if let InField = self.state {
+                    if self.is_blank_line() {
+                        self.fieldbuf.clear();
+                        self.state = StartRecord;
+                    }
+                }
+                if let StartRecord = self.state {
+                    return self.next_eoc();
+                } else if let EndRecord = self.state {
+                    self.state = StartRecord;
+                    return self.next_eor();
+                } else {
+                    self.term_len = 0;
+                    self.state = EndRecord;
+                    return self.next_data();
+                }
+            }
+            while self.bufi < self.buf.len() {
+                let c = self.buf[self.bufi];
+                if self.cr_pending {
+                    self.cr_pending = false;
+                    if c == b'\n' {
+                        self.bump();
+                        self.term[1] = b'\n';
+                        self.term_len = 2;
+                        continue;
+                    }
+                }
+                match self.state {
+                    StartRecord => {
+                        if self.skip_lines > 0 {
+                            self.bump();
+                            if self.is_record_term(c) {
+                                self.bump_eor(c);
+                                self.skip_lines -= 1;
+                            }
+                        } else if self.is_record_term(c) {
+                            self.bump();
+                        } else if self.comment == Some(c) {
+                            self.bump();
+                            self.state = InComment;
+                        } else if self.leading_width > 0 {
+                            self.start_record();
+                            self.leading.clear();
+                            self.state = InLeading;
+                        } else {
+                            self.start_record();
+                            self.state = StartField;
+                        }
+                    }
+                    InComment => {
+                        self.bump();
+                        if self.is_record_term(c) {
+                            self.state = StartRecord;
+                        }
+                    }
+                    InLeading => {
+                        self.bump();
+                        if self.is_record_term(c) {
+                            self.bump_eor(c);
+                            self.state = EndRecord;
+                            return self.next_data();
+                        }
+                        self.leading.push(c);
+                        if self.leading.len() == self.leading_width {
+                            self.state = StartField;
+                        }
+                    }
+                    EndRecord => {
+                        self.state = StartRecord;
+                        return self.next_eor();
+                    }
+                    StartField => {
+                        self.bump();
+                        self.field_quoted = false;
+                        self.field_starts_quoted =
+                            c == self.quote || self.alt_quote == Some(c);
+                        if self.is_unquoted_escape(c) {
+                            self.add_quote(c);
+                            self.field_quoted = true;
+                            self.state = InUnquotedEscape;
+                        } else if self.field_starts_quoted {
+                            self.cur_close = if c == self.quote {
+                                self.close_quote
+                            } else {
+                                c
+                            };
+                            self.add_quote(c);
+                            self.field_quoted = true;
+                            self.field_lines = 0;
+                            self.state = InQuotedField;
+                        } else if c == self.delimiter {
+                            return self.next_data();
+                        } else if self.is_record_term(c) {
+                            self.bump_eor(c);
+                            self.state = EndRecord;
+                            return self.next_data();
+                        } else {
+                            self.add(c);
+                            self.state = InField;
+                        }
+                    }
+                    InField => {
+                        self.bump();
+                        if self.is_unquoted_escape(c) {
+                            self.add_quote(c);
+                            self.field_quoted = true;
+                            self.state = InUnquotedEscape;
+                        } else if c == self.delimiter {
+                            self.state = StartField;
+                            return self.next_data();
+                        } else if self.is_record_term(c) {
+                            self.bump_eor(c);
+                            if self.is_blank_line() {
+                                self.fieldbuf.clear();
+                                self.state = StartRecord;
+                                continue;
+                            }
+                            self.state = EndRecord;
+                            return self.next_data();
+                        } else {
+                            self.add(c);
+                        }
+                    }
+                    InQuotedField => {
+                        self.bump();
+                        if c == self.cur_close {
+                            self.add_quote(c);
+                            self.state = InDoubleEscapedQuote;
+                        } else if self.escape == Some(c) {
+                            self.add_quote(c);
+                            self.state = InEscapedQuote;
                         } else {
+                            if self.is_field_line_term(c) {
+                                self.field_lines += 1;
+                                if let Some(max) = self.max_field_lines {
+                                    if self.field_lines > max {
+                                        return self.parse_error(
+                                            ParseError::FieldTooManyLines {
+                                                max: max,
+                                            });
+                                    }
+                                }
+                            }
+                            self.add(c);
+                        }
+                    }
+                    InEscapedQuote => {
+                        self.bump();
+                        self.state = InQuotedField;
+                        if c != self.cur_close && self.escape != Some(c) {
+                            if self.strict_escape {
+                                let pos = self.byte_offset - 2;
+                                return self.parse_error(
+                                    ParseError::InvalidEscape { pos: pos });
+                            }
+                            if !self.preserve_quotes {
+                                let escape = self.escape.unwrap();
+                                self.add(escape);
+                            }
+                        }
+                        self.add(c);
+                    }
+                    InUnquotedEscape => {
+                        self.bump();
+                        self.add(c);
+                        self.state = InField;
+                    }
+                    InDoubleEscapedQuote => {
+                        self.bump();
+                        if self.double_quote && c == self.cur_close {
+                            self.add(c);
+                            self.state = InQuotedField;
+                        } else if c == self.delimiter {
                             self.state = StartField;
+                            return self.next_data();
+                        } else if self.is_record_term(c) {
+                            self.bump_eor(c);
+                            self.state = EndRecord;
+                            return self.next_data();
+                        } else if self.trailing_quote_error {
+                            self.add(c);
+                            self.state = InField;
+                            let pos = self.byte_offset - 1;
+                            return self.parse_error(
+                                ParseError::TrailingQuoteContent {
+                                    pos: pos,
+                                });
+                        } else {
+                            if self.relaxed_quotes && !self.preserve_quotes {
+                                let quote = self.cur_close;
+                                self.add(quote);
+                            }
+                            self.add(c);
+                            self.state = InField; // degrade gracefully?
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// This is just like `next_bytes` except it converts each field to
+    /// a Unicode string in place.
+    pub fn next_str(&mut self) -> NextField<str> {
+        // This really grates me. Once we call `next_bytes`, we initiate a
+        // *mutable* borrow of `self` that doesn't stop until the return value
+        // goes out of scope. Since we have to return that value, it will never
+        // go out of scope in this function.
+        //
+        // Therefore, we can't get access to any state information after
+        // calling `next_bytes`. But we might need it to report an error.
+        //
+        // One possible way around this is to use interior mutability...
+        let (record, field) = (self.irecord, self.ifield);
+        match self.next_bytes() {
+            NextField::EndOfRecord => NextField::EndOfRecord,
+            NextField::EndOfCsv => NextField::EndOfCsv,
+            NextField::Error(err) => NextField::Error(err),
+            NextField::Data(bytes) => {
+                match str::from_utf8(bytes) {
+                    Ok(s) => NextField::Data(s),
+                    Err(_) => NextField::Error(Error::Parse(LocatableError {
+                        record: record,
+                        field: field,
+                        err: ParseError::InvalidUtf8,
+                    })),
+                }
+            }
+        }
+    }
+
+    /// Reads the next record into caller supplied buffers.
+    ///
+    /// The bytes of every field are written contiguously to `fields`, and
+    /// the end offset (in `fields`) of each field is written to `ends`. No
+    /// allocation is performed and neither buffer is ever grown. Instead,
+    /// if either buffer is too small, then `OutputFull` or `OutputEndsFull`
+    /// is returned.
+    ///
+    /// Every variant of `ReadInto` reports the number of bytes written to
+    /// `fields` and the number of field ends written to `ends` by *this*
+    /// call. To resume after `OutputFull` or `OutputEndsFull`, call this
+    /// method again with more room, e.g., with the unused tails of the same
+    /// buffers. The partially read record is continued where it left off,
+    /// and all offsets reported are relative to the new buffers. A field
+    /// that was cut short by `OutputFull` has its remaining bytes written
+    /// first.
+    ///
+    /// Like `next_bytes`, this returns all records (i.e., it won't skip the
+    /// header row). Mixing this with other methods that read records while
+    /// a record is only partially read is not supported.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use csv::ReadInto;
+    ///
+    /// let mut rdr = csv::Reader::from_string("abc,xyz\n");
+    /// let (mut fields, mut ends) = ([0; 4], [0; 2]);
+    ///
+    /// let r = rdr.read_record_into(&mut fields, &mut ends).unwrap();
+    /// assert_eq!(r, ReadInto::OutputFull { fields: 1, bytes: 4 });
+    /// assert_eq!(&fields[..ends[0]], b"abc");
+    ///
+    /// let r = rdr.read_record_into(&mut fields, &mut ends).unwrap();
+    /// assert_eq!(r, ReadInto::Record { fields: 1, bytes: 2 });
+    /// assert_eq!(&fields[..ends[0]], b"yz");
+    ///
+    /// let r = rdr.read_record_into(&mut fields, &mut ends).unwrap();
+    /// assert_eq!(r, ReadInto::End);
+    /// ```
+    pub fn read_record_into(
+        &mut self,
+        fields: &mut [u8],
+        ends: &mut [usize],
+    ) -> Result<ReadInto> {
+        let (mut nbytes, mut nends) = (0, 0);
+        loop {
+            if let Some((start, end)) = self.into_field.take() {
+                if nends == ends.len() {
+                    self.into_field = Some((start, end));
+                    return Ok(ReadInto::OutputEndsFull {
+                        fields: nends,
+                        bytes: nbytes,
+                    });
+                }
+                let n = cmp::min(end - start, fields.len() - nbytes);
+                fields[nbytes..nbytes + n]
+                    .copy_from_slice(&self.fieldbuf[start..start + n]);
+                nbytes += n;
+                if start + n < end {
+                    self.into_field = Some((start + n, end));
+                    return Ok(ReadInto::OutputFull {
+                        fields: nends,
+                        bytes: nbytes,
+                    });
+                }
+                ends[nends] = nbytes;
+                nends += 1;
+            }
+            match self.next_bytes() {
+                NextField::Data(_) => {}
+                NextField::Error(err) => return Err(err),
+                NextField::EndOfCsv if nends == 0 => return Ok(ReadInto::End),
+                NextField::EndOfRecord | NextField::EndOfCsv => {
+                    return Ok(ReadInto::Record {
+                        fields: nends,
+                        bytes: nbytes,
+                    });
+                }
+            }
+            self.into_field = Some(self.trimmed());
+        }
+    }
+
+    /// An unsafe iterator over byte fields.
+    ///
+    /// This iterator calls `next_bytes` at each step.
+    ///
+    /// It is (wildly) unsafe because the lifetime yielded for each element
+    /// is incorrect. It refers to the lifetime of the CSV reader instead of
+    /// the lifetime of the internal buffer. Which means you can `collect`
+    /// it into a vector and obliterate memory safety.
+    ///
+    /// The reason it exists is because it appears extremely difficult to write
+    /// a fast streaming iterator. (But iterators are wildly convenient.)
+    #[doc(hidden)]
+    pub unsafe fn byte_fields<'a>(&'a mut self) -> UnsafeByteFields<'a, R> {
+        UnsafeByteFields { rdr: self }
+    }
+
+    /// Returns the total number of bytes parsed by this reader.
+    ///
+    /// Unlike `byte_offset`, this is never reset by seeking. Namely, bytes
+    /// that are parsed again after seeking backwards are counted again. This
+    /// makes it suitable for measuring throughput.
+    pub fn total_bytes_read(&self) -> u64 {
+        self.bytes_before_seek + (self.byte_offset - self.seek_offset)
+    }
+
+    /// Returns the number of fields parsed so far in the current record.
+    ///
+    /// This is meant for diagnostics right after an error, when it is the
+    /// number of fields of the record that caused it that were parsed. For
+    /// example, after a `TooManyFields` error it is the field limit, and
+    /// after an `UnequalLengths` error it is the length of the offending
+    /// record. It is also the number of fields returned so far when reading
+    /// a record with `next_bytes`. Between records, it is `0`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2,3\n");
+    /// assert!(rdr.records().next().unwrap().is_err());
+    /// assert_eq!(rdr.partial_field_count(), 3);
+    /// ```
+    pub fn partial_field_count(&self) -> usize {
+        self.ifield as usize
+    }
+
+    /// Returns a shared counter of the number of bytes parsed.
+    ///
+    /// The reader adds to the counter at the end of every record, so it
+    /// always equals `total_bytes_read` as of the last complete record.
+    /// Since the counter is shared, another part of a program (e.g., one
+    /// that draws a progress bar) can read it without borrowing the reader.
+    ///
+    /// Counting is opt-in: nothing is counted until this is called. Every
+    /// call returns the same counter, and bytes parsed before the first
+    /// call are included. Updates are relaxed atomic additions, so they are
+    /// cheap, but they don't synchronize any other memory.
+    ///
+    /// The counter is an `AtomicUsize` because `AtomicU64` isn't available
+    /// on Rust 1.12, the oldest version this crate supports. On 32-bit
+    /// targets, this means that the counter wraps around to 0 after 4 GiB
+    /// have been parsed.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use std::sync::atomic::Ordering;
+    ///
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2\n");
+    /// let progress = rdr.progress_counter();
+    /// rdr.headers().unwrap();
+    /// assert_eq!(progress.load(Ordering::Relaxed), 4);
+    /// rdr.collect_records().unwrap();
+    /// assert_eq!(progress.load(Ordering::Relaxed), 8);
+    /// ```
+    pub fn progress_counter(&mut self) -> Arc<AtomicUsize> {
+        if let Some(ref progress) = self.progress {
+            return progress.clone();
+        }
+        let progress = Arc::new(AtomicUsize::new(0));
+        self.progress = Some(progress.clone());
+        self.report_progress();
+        progress
+    }
+
+    /// Adds the bytes parsed since the last report to `progress`.
+    fn report_progress(&mut self) {
+        let total = self.total_bytes_read();
+        if let Some(ref progress) = self.progress {
+            let n = (total - self.progress_base) as usize;
+            progress.fetch_add(n, Ordering::Relaxed);
+        }
+        self.progress_base = total;
+    }
+
+    /// Returns the byte offset at which the current record started.
+    ///
+    /// More precisely, this is the number of bytes the parser has consumed.
+    /// Between records (e.g., after a record iterator yields a record), this
+    /// is the offset at which the next record starts. When reading fields
+    /// with `next_bytes`, it is updated as each field is parsed, so it can
+    /// also be used to track progress within a record.
+    pub fn byte_offset(&self) -> u64 {
+        self.byte_offset
+    }
+
+    /// Returns the record terminator that ended the most recent record.
+    ///
+    /// These are the exact bytes that followed the last field of the record,
+    /// e.g., `\r\n` or `\n` with the default `CRLF` terminator. If the record
+    /// was ended by the end of the data instead, then this is empty. Together
+    /// with `preserve_quotes`, this makes it possible to reconstruct the CSV
+    /// data byte for byte.
+    ///
+    /// This is only valid after a record has been read (e.g., after a record
+    /// iterator yields a record, or after `next_bytes` returns
+    /// `EndOfRecord`) and until the next record is read. Seeking clears it.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a\r\nb\nc")
+    ///                           .has_headers(false);
+    /// let mut terms = vec![];
+    /// while let Some(row) = rdr.records().next() {
+    ///     row.unwrap();
+    ///     terms.push(rdr.last_terminator_bytes().to_vec());
+    /// }
+    /// assert_eq!(terms, vec![b"\r\n".to_vec(), b"\n".to_vec(), vec![]]);
+    /// ```
+    pub fn last_terminator_bytes(&self) -> &[u8] {
+        &self.term[..self.term_len]
+    }
+
+    /// Returns the fixed-width prefix of the most recent record.
+    ///
+    /// This is empty unless `leading_fixed` is set. It is valid after a
+    /// record has been read until the next record is read.
+    pub fn last_leading(&self) -> &[u8] {
+        &self.leading
+    }
+
+    /// Returns whether each field of the most recent record was quoted.
+    ///
+    /// This makes it possible to tell an empty unquoted field (e.g., the
+    /// middle field of `a,,b`) from an empty quoted field (`a,"",b`), which
+    /// some databases treat as `NULL` and an empty string, respectively.
+    ///
+    /// This is always empty unless `track_quotes` is enabled, since that
+    /// metadata isn't kept otherwise. It is valid after a record has been
+    /// read until the next record is read.
+    ///
+    /// The flags line up with the fields of the record as it is returned
+    /// by the record iterators, i.e., after `project` and `add_column` have
+    /// been applied. Added columns are never quoted, and neither are
+    /// projected columns that the record doesn't have.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,,\"\"")
+    ///                           .has_headers(false)
+    ///                           .track_quotes(true);
+    /// let row = rdr.records().next().unwrap().unwrap();
+    /// assert_eq!(row, vec!["a", "", ""]);
+    /// assert_eq!(rdr.last_record_quoted(), &[false, false, true]);
+    /// ```
+    pub fn last_record_quoted(&self) -> &[bool] {
+        if self.reshapes() {
+            &self.shaped_quoted
+        } else {
+            &self.record_quoted
+        }
+    }
+
+    /// Reads the rest of the CSV data and returns the byte offset of every
+    /// `stride`-th record.
+    ///
+    /// The first offset is that of the next record, and then one every
+    /// `stride` records after that. An offset is only returned for records
+    /// that exist, so the records of a last partial stride aren't included
+    /// unless one lands exactly on a multiple of `stride`. For example, with
+    /// a `stride` of `2`, the offsets of records `0`, `2` and `4` of five
+    /// records are returned.
+    ///
+    /// If the header row hasn't been read yet and `has_headers` is enabled,
+    /// then it is skipped first. Records that have already been parsed
+    /// (e.g., by peeking) aren't included.
+    ///
+    /// This is faster than reading records just to find their offsets,
+    /// since no fields are copied. Each offset may be passed to `seek` for
+    /// (approximate) random access to a large file, and the offsets may be
+    /// persisted for later use. For an index of every record, see the
+    /// `csv::index` module.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `stride` is `0`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let data = "h\na\nb\nc\nd\ne\n";
+    /// let mut rdr = csv::Reader::from_string(data);
+    /// let offsets = rdr.index_every(2).unwrap();
+    /// assert_eq!(offsets, vec![2, 6, 10]);
+    ///
+    /// rdr.seek(offsets[1]).unwrap();
+    /// let rows = rdr.collect_records().unwrap();
+    /// assert_eq!(rows, vec![vec!["c"], vec!["d"], vec!["e"]]);
+    /// ```
+    pub fn index_every(&mut self, stride: u64) -> Result<Vec<u64>> {
+        assert!(stride > 0, "index stride must be greater than 0");
+        if !self.first_handled {
+            self.first_handled = true;
+            if self.has_headers {
+                try!(self.byte_headers());
+            }
+        }
+        self.peeked = None;
+        let mut offsets = vec![];
+        // The number of records to pass before the next indexed one.
+        let mut skip = 0u64;
+        while !self.done() {
+            let offset = self.byte_offset;
+            loop {
+                match self.next_bytes() {
+                    NextField::EndOfCsv => break,
+                    NextField::EndOfRecord => {
+                        if skip == 0 {
+                            offsets.push(offset);
+                            skip = stride;
                         }
+                        skip -= 1;
+                        break;
                     }
-                    EndRecord => {
-                        self.state = StartRecord;
-                        return self.next_eor();
+                    NextField::Error(err) => return Err(err),
+                    NextField::Data(_) => {}
+                }
+            }
+        }
+        Ok(offsets)
+    }
+
+    /// Reads the single field of the next record into `buf`.
+    ///
+    /// This is a fast path for CSV data with exactly one column, like a word
+    /// list or a list of IDs. Unlike the record iterators, no record is
+    /// allocated: `buf` is cleared and the field is written to it, so the
+    /// same `buf` can be reused for every record. Quoting is still honored,
+    /// so a quoted field may contain delimiters and line terminators.
+    ///
+    /// `true` is returned if a field was read, and `false` if there are no
+    /// more records. Like the record iterators, the header row is skipped if
+    /// `has_headers` is enabled.
+    ///
+    /// A record with more than one field results in an `UnequalLengths`
+    /// error, even if `flexible` is enabled. The rest of that record is
+    /// skipped, so reading may continue with the next record. A field that
+    /// isn't valid UTF-8 also results in an error.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("id\nfoo\n\"b\nar\"\n");
+    /// let mut buf = String::new();
+    ///
+    /// assert!(rdr.read_single_column(&mut buf).unwrap());
+    /// assert_eq!(buf, "foo");
+    /// assert!(rdr.read_single_column(&mut buf).unwrap());
+    /// assert_eq!(buf, "b\nar");
+    /// assert!(!rdr.read_single_column(&mut buf).unwrap());
+    /// ```
+    pub fn read_single_column(&mut self, buf: &mut String) -> Result<bool> {
+        buf.clear();
+        let record = match self.pending_record() {
+            None => None,
+            Some(record) => Some(try!(record)),
+        };
+        let mut fields = match record {
+            None => 0,
+            Some(record) => {
+                if record.len() != 1 {
+                    return Err(self.single_column_error(
+                        self.irecord - 1, record.len() as u64));
+                }
+                buf.push_str(&try!(byte_record_to_utf8(record))[0]);
+                return Ok(true);
+            }
+        };
+        loop {
+            let irecord = self.irecord;
+            match self.next_bytes() {
+                NextField::EndOfCsv => return Ok(fields > 0),
+                NextField::EndOfRecord => break,
+                NextField::Error(err) => return Err(err),
+                NextField::Data(field) => {
+                    fields += 1;
+                    if fields > 1 {
+                        continue;
+                    }
+                    match str::from_utf8(field) {
+                        Ok(field) => buf.push_str(field),
+                        Err(_) => {
+                            return Err(Error::Parse(LocatableError {
+                                record: irecord,
+                                field: 0,
+                                err: ParseError::InvalidUtf8,
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+        if fields > 1 {
+            buf.clear();
+            return Err(self.single_column_error(self.irecord - 1, fields));
+        }
+        Ok(true)
+    }
+
+    fn single_column_error(&self, irecord: u64, got: u64) -> Error {
+        Error::Parse(LocatableError {
+            record: irecord,
+            field: got,
+            err: ParseError::UnequalLengths {
+                expected: 1,
+                got: got,
+            },
+        })
+    }
+
+    /// Reads the rest of the CSV data and returns statistics about its
+    /// records.
+    ///
+    /// This consumes the stream: when it returns, all records have been
+    /// read. Like `index_every`, it skips the header row first if it hasn't
+    /// been read yet and `has_headers` is enabled, and it doesn't copy any
+    /// fields.
+    ///
+    /// The size of a record is the number of raw bytes it spans in the CSV
+    /// data, including quotes, delimiters and its record terminator. (Any
+    /// empty lines or comment lines before a record are counted as part of
+    /// it.)
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("h1,h2\na,b\nccc,d,e\n")
+    ///                           .flexible(true);
+    /// let stats = rdr.stats().unwrap();
+    /// assert_eq!(stats.records, 2);
+    /// assert_eq!(stats.min_record_bytes, 4);
+    /// assert_eq!(stats.max_record_bytes, 8);
+    /// assert_eq!(stats.mean_record_bytes, 6.0);
+    /// assert_eq!(stats.max_fields, 3);
+    /// ```
+    pub fn stats(&mut self) -> Result<ReaderStats> {
+        if !self.first_handled {
+            self.first_handled = true;
+            if self.has_headers {
+                try!(self.byte_headers());
+            }
+        }
+        self.peeked = None;
+        let mut stats = ReaderStats::default();
+        let start = self.byte_offset;
+        while !self.done() {
+            let offset = self.byte_offset;
+            let mut fields = 0;
+            loop {
+                match self.next_bytes() {
+                    NextField::EndOfCsv => break,
+                    NextField::EndOfRecord => {
+                        let len = self.byte_offset - offset;
+                        if stats.records == 0 || len < stats.min_record_bytes {
+                            stats.min_record_bytes = len;
+                        }
+                        stats.max_record_bytes =
+                            cmp::max(stats.max_record_bytes, len);
+                        stats.max_fields = cmp::max(stats.max_fields, fields);
+                        stats.records += 1;
+                        break;
+                    }
+                    NextField::Error(err) => return Err(err),
+                    NextField::Data(_) => fields += 1,
+                }
+            }
+        }
+        stats.total_bytes = self.byte_offset - start;
+        if stats.records > 0 {
+            stats.mean_record_bytes =
+                stats.total_bytes as f64 / stats.records as f64;
+        }
+        Ok(stats)
+    }
+
+    /// Infers the type of each column from the next `max_rows` records.
+    ///
+    /// Every field of a column is tried as an integer (`i64`), a float
+    /// (`f64`) and a boolean (`true` or `false`), in that order. A column
+    /// gets the narrowest kind that fits all of its non-empty fields: a
+    /// column of integers and floats is a `Float` column, and a column that
+    /// mixes any other kinds is a `Text` column. Columns with no non-empty
+    /// fields at all are `Text` columns too.
+    ///
+    /// A column is nullable if any of its fields is empty (or missing from
+    /// a short record, when `flexible` is enabled). If `has_headers` is
+    /// enabled, then each column is named after its header.
+    ///
+    /// The sampled records are consumed, so they aren't returned by the
+    /// record iterators afterwards. With a seekable reader, `seek_to_start`
+    /// can be used to read them again.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use csv::ColumnKind;
+    ///
+    /// let data = "id,price,name,ok\n1,2.5,foo,true\n2,3,,false\n";
+    /// let mut rdr = csv::Reader::from_string(data);
+    /// let schema = rdr.infer_schema(100).unwrap();
+    ///
+    /// let kinds: Vec<_> = schema.iter().map(|c| c.kind).collect();
+    /// assert_eq!(kinds, vec![ColumnKind::Int, ColumnKind::Float,
+    ///                        ColumnKind::Text, ColumnKind::Bool]);
+    /// assert_eq!(schema[2].name, Some("name".to_string()));
+    /// assert!(schema[2].nullable);
+    /// ```
+    pub fn infer_schema(
+        &mut self,
+        max_rows: usize,
+    ) -> Result<Vec<ColumnType>> {
+        let names = if self.has_headers {
+            try!(self.headers())
+        } else {
+            vec![]
+        };
+        let mut kinds: Vec<Option<ColumnKind>> = vec![];
+        let mut nullable: Vec<bool> = vec![];
+        let mut rows = 0;
+        for record in self.byte_records().take(max_rows) {
+            let record = try!(record);
+            if record.len() > kinds.len() {
+                // A new column is missing from all previous records.
+                kinds.resize(record.len(), None);
+                nullable.resize(record.len(), rows > 0);
+            }
+            for (i, field) in record.iter().enumerate() {
+                if field.is_empty() {
+                    nullable[i] = true;
+                    continue;
+                }
+                let kind = ColumnKind::of(field);
+                kinds[i] = Some(match kinds[i] {
+                    None => kind,
+                    Some(prev) => prev.merge(kind),
+                });
+            }
+            for null in nullable.iter_mut().skip(record.len()) {
+                *null = true;
+            }
+            rows += 1;
+        }
+        let ncols = cmp::max(kinds.len(), names.len());
+        Ok((0..ncols).map(|i| {
+            ColumnType {
+                name: names.get(i).cloned(),
+                kind: kinds.get(i).and_then(|&k| k)
+                           .unwrap_or(ColumnKind::Text),
+                nullable: nullable.get(i).cloned().unwrap_or(true),
+            }
+        }).collect())
+    }
+
+    /// Returns a record that must be yielded before parsing any further.
+    ///
+    /// This is either a record that was peeked at or, if the record
+    /// iterators haven't handled it yet, the first record when this CSV
+    /// data has no headers.
+    fn pending_record(&mut self) -> Option<Result<Vec<ByteString>>> {
+        if let Some(record) = self.peeked.take() {
+            return Some(Ok(record));
+        }
+        // We check this before checking `done` because the parser could
+        // be done after a call to `byte_headers` but before any iterator
+        // traversal. Once we start iterating, we must allow the first
+        // row to be returned if the caller has said that this CSV data
+        // has no headers.
+        if self.first_handled {
+            return None;
+        }
+        // Never do this special first record processing again.
+        self.first_handled = true;
+
+        // Always consume the header record. This let's us choose to
+        // return it or ignore it and move on to the next record.
+        // If headers have been read before this point, then this is
+        // equivalent to a harmless clone (and no parser progression).
+        let headers = self.byte_headers();
+
+        // If the header row is empty, then the CSV data contains
+        // no records. Never return zero-length records!
+        if headers.as_ref().map(|r| r.is_empty()).unwrap_or(false) {
+            assert!(self.done());
+            return None;
+        }
+
+        // This is important. If the client says this CSV data has no
+        // headers but calls `headers` before iterating records (which is
+        // perfectly valid), then we need to make sure to return that
+        // first record.
+        //
+        // If the client says the CSV data has headers, then the first
+        // record should always be ignored.
+        if !self.has_headers {
+            if self.reshapes() {
+                self.unshaped = Some(self.first_row.clone());
+            }
+            return Some(headers);
+        }
+        None
+    }
+
+    /// Reads the next record for the record iterators.
+    fn next_byte_record(&mut self) -> Option<Result<Vec<ByteString>>> {
+        if let Some(record) = self.pending_record() {
+            return Some(record);
+        }
+        // OK, we're done checking the weird first-record-corner-case.
+        if self.done() {
+            return None;
+        }
+        let mut record = Vec::with_capacity(self.first_row.len());
+        loop {
+            match self.next_bytes() {
+                NextField::EndOfRecord | NextField::EndOfCsv => {
+                    if record.len() == 0 {
+                        return None
+                    }
+                    break
+                }
+                NextField::Error(err) => return Some(Err(err)),
+                NextField::Data(field) => record.push(field.to_vec()),
+            }
+        }
+        let irecord = self.irecord - 1;
+        if self.reshapes() {
+            self.unshaped = Some(record.clone());
+        }
+        let mut record = match self.project_record(record, irecord) {
+            Err(err) => return Some(Err(err)),
+            Ok(record) => record,
+        };
+        let offset = self.record_start;
+        self.add_columns(&mut record, irecord, offset);
+        self.shape_quoted();
+        Some(Ok(record))
+    }
+
+    /// Returns true if records are changed by `project` or `add_column`
+    /// after they are parsed.
+    fn reshapes(&self) -> bool {
+        self.projection.is_some() || !self.added.is_empty()
+    }
+
+    /// Lines up the quote flags of the record that was just parsed with its
+    /// fields after `project` and `add_column` were applied.
+    fn shape_quoted(&mut self) {
+        if !self.track_quotes || !self.reshapes() {
+            return;
+        }
+        let mut shaped = match self.projection {
+            None => self.record_quoted.clone(),
+            Some(ref cols) => cols.iter().map(|&i| {
+                self.record_quoted.get(i).cloned().unwrap_or(false)
+            }).collect(),
+        };
+        shaped.extend(self.added.iter().map(|_| false));
+        self.shaped_quoted = shaped;
+    }
+
+    /// Appends the columns set with `add_column` to a record.
+    ///
+    /// `irecord` is the number of the record and `offset` is the byte
+    /// offset at which it starts.
+    fn add_columns(
+        &mut self,
+        record: &mut Vec<ByteString>,
+        irecord: u64,
+        offset: u64,
+    ) {
+        for &mut (_, ref mut source) in &mut self.added {
+            let value = match *source {
+                ColumnSource::Value(ref v) => v.clone().into_bytes(),
+                ColumnSource::RecordNumber => {
+                    irecord.to_string().into_bytes()
+                }
+                ColumnSource::ByteOffset => offset.to_string().into_bytes(),
+                ColumnSource::With(ref mut f) => f(record),
+            };
+            record.push(value);
+        }
+    }
+
+    /// Selects the projected columns (if any) from a record.
+    ///
+    /// `irecord` is the number of the record, for error reporting.
+    fn project_record(
+        &self,
+        record: Vec<ByteString>,
+        irecord: u64,
+    ) -> Result<Vec<ByteString>> {
+        let cols = match self.projection {
+            None => return Ok(record),
+            Some(ref cols) => cols,
+        };
+        let mut projected = Vec::with_capacity(cols.len());
+        for &i in cols {
+            match record.get(i) {
+                Some(field) => projected.push(field.clone()),
+                None if !self.projection_strict => projected.push(vec![]),
+                None => {
+                    return Err(Error::Parse(LocatableError {
+                        record: irecord,
+                        field: i as u64 + 1,
+                        err: ParseError::MissingColumn { column: i as u64 },
+                    }));
+                }
+            }
+        }
+        Ok(projected)
+    }
+
+    /// Reads the next record for the record iterators ahead of time.
+    fn peek_byte_record(&mut self) -> Result<Option<&[ByteString]>> {
+        if self.peeked.is_none() {
+            match self.next_byte_record() {
+                None => return Ok(None),
+                Some(Err(err)) => return Err(err),
+                Some(Ok(record)) => self.peeked = Some(record),
+            }
+        }
+        Ok(self.peeked.as_ref().map(|r| &r[..]))
+    }
+
+    /// Returns true if the record that caused `err` should be skipped.
+    ///
+    /// If so, the parser is made ready to read the next record.
+    fn skip_error(&mut self, err: &Error) -> bool {
+        match self.on_error {
+            Some(OnError::Skip) | Some(OnError::Lossy) => {}
+            _ if self.error_sink.is_some() => {}
+            _ => return false,
+        }
+        match *err {
+            Error::Parse(LocatableError {
+                err: ParseError::UnequalLengths { .. }, ..
+            }) => {
+                // The whole record has been parsed, but `next_eor` bailed
+                // before moving on to the next one.
+                self.irecord += 1;
+                self.ifield = 0;
+                if let Some(ref mut sink) = self.error_sink {
+                    sink(err);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Puts a record back so that the record iterators return it next.
+    fn put_back(&mut self, record: Vec<String>) {
+        self.peeked = Some(
+            record.into_iter().map(String::into_bytes).collect());
+    }
+
+    #[inline]
+    fn next_data(&mut self) -> NextField<[u8]> {
+        if let Some(max) = self.max_fields {
+            if self.ifield >= max {
+                return self.parse_error(
+                    ParseError::TooManyFields { max: max });
+            }
+        }
+        if self.track_quotes {
+            if self.ifield == 0 {
+                self.record_quoted.clear();
+            }
+            let quoted = self.field_starts_quoted;
+            self.record_quoted.push(quoted);
+        }
+        self.field_starts_quoted = false;
+        let (start, end) = self.trimmed();
+        if self.capture_raw {
+            if self.ifield == 0 {
+                self.raw_record.clear();
+                self.raw_done = false;
+            }
+            self.raw_record.push(self.fieldbuf[start..end].to_vec());
+        }
+        if !self.first_row_done {
+            self.first_row.push(self.fieldbuf[start..end].to_vec());
+        }
+        self.ifield += 1;
+        NextField::Data(&self.fieldbuf[start..end])
+    }
+
+    /// Returns the bounds of the current field after trimming.
+    #[inline]
+    fn trimmed(&self) -> (usize, usize) {
+        if self.trim_chars.is_empty() {
+            return (0, self.fieldbuf.len());
+        }
+        let trim = |b: &u8| !self.trim_chars.contains(b);
+        let start = self.fieldbuf.iter().position(&trim);
+        let end = self.fieldbuf.iter().rposition(&trim);
+        match (start, end) {
+            (Some(start), Some(end)) => (start, end + 1),
+            _ => (0, 0),
+        }
+    }
+
+    #[inline]
+    fn next_eor(&mut self) -> NextField<[u8]> {
+        if self.capture_raw {
+            self.save_raw_bytes();
+        }
+        if self.progress.is_some() {
+            self.report_progress();
+        }
+        if !self.flexible && self.first_row_done {
+            let expected = if self.header_excluded && self.has_headers {
+                // The first data record sets the length for all others.
+                match self.data_len {
+                    Some(len) => len,
+                    None => {
+                        self.data_len = Some(self.ifield);
+                        self.ifield
                     }
-                    StartField => {
-                        self.bump();
-                        if c == self.quote {
-                            self.state = InQuotedField;
-                        } else if c == self.delimiter {
-                            return self.next_data();
-                        } else if self.is_record_term(c) {
-                            self.bump_eor(c);
-                            self.state = EndRecord;
-                            return self.next_data();
-                        } else {
-                            self.add(c);
-                            self.state = InField;
-                        }
+                }
+            } else {
+                self.first_row.len() as u64
+            };
+            if self.ifield != expected {
+                if self.capture_raw {
+                    if self.ifield == 0 {
+                        self.raw_record.clear();
                     }
-                    InField => {
+                    self.raw_done = true;
+                }
+                return self.parse_error(ParseError::UnequalLengths {
+                    expected: expected,
+                    got: self.ifield as u64,
+                });
+            }
+        }
+        if !self.first_row_done {
+            self.first_row_start = self.record_start;
+        }
+        if !self.first_row_done && self.has_headers {
+            if let Some(ref mut on_header) = self.on_header {
+                for (i, field) in self.first_row.iter().enumerate() {
+                    on_header(i, &String::from_utf8_lossy(field));
+                }
+            }
+        }
+        self.irecord += 1;
+        self.ifield = 0;
+        self.first_row_done = true;
+        self.raw_done = self.capture_raw;
+        NextField::EndOfRecord
+    }
+
+    /// Returns true if `read_limit` bytes have been parsed.
+    fn at_read_limit(&self) -> bool {
+        match self.read_limit {
+            None => false,
+            Some(limit) => {
+                self.total_bytes_read() - self.read_limit_base >= limit
+            }
+        }
+    }
+
+    #[inline]
+    fn next_eoc(&self) -> NextField<[u8]> {
+        NextField::EndOfCsv
+    }
+
+    #[inline]
+    fn fill_buf(&mut self) -> io::Result<()> {
+        if self.bufi == self.buf.len() {
+            unsafe { let cap = self.buf.capacity(); self.buf.set_len(cap); }
+            let n = self.rdr.read(&mut self.buf);
+            // On error, leave the buffer empty so that stale data is never
+            // parsed.
+            unsafe { self.buf.set_len(*n.as_ref().unwrap_or(&0)); }
+            self.bufi = 0;
+            try!(n);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn start_record(&mut self) {
+        self.record_start = self.byte_offset;
+        if self.capture_raw {
+            self.raw_bytes.clear();
+            self.raw_from = self.bufi;
+            self.raw_done = false;
+        }
+    }
+
+    /// Copies the bytes of the current record parsed so far out of the
+    /// buffer.
+    fn save_raw_bytes(&mut self) {
+        let from = cmp::min(self.raw_from, self.bufi);
+        self.raw_bytes.extend_from_slice(&self.buf[from..self.bufi]);
+        self.raw_from = self.bufi;
+    }
+
+    #[inline]
+    fn bump(&mut self) {
+        self.bufi += 1;
+        self.byte_offset += 1;
+    }
+
+    #[inline]
+    fn bump_eor(&mut self, c: u8) {
+        self.term = [c, 0];
+        self.term_len = 1;
+        if !self.record_term.is_crlf() || c != b'\r' {
+            return;
+        }
+        match self.buf.get(self.bufi) {
+            Some(&b'\n') => {
+                self.bump();
+                self.term[1] = b'\n';
+                self.term_len = 2;
+            }
+            None => self.cr_pending = true,
+            Some(_) => {}
+        }
+    }
+
+    #[inline]
+    fn add(&mut self, c: u8) {
+        self.fieldbuf.push(c);
+    }
+
+    /// Adds a quote or escape byte only if quotes are being preserved.
+    #[inline]
+    fn add_quote(&mut self, c: u8) {
+        if self.preserve_quotes {
+            self.fieldbuf.push(c);
+        }
+    }
+
+    /// Returns true if the current field is a line to skip because it only
+    /// contains whitespace.
+    fn is_blank_line(&self) -> bool {
+        self.ignore_blank_lines
+        && self.ifield == 0
+        && !self.field_quoted
+        && self.fieldbuf.iter().all(|&b| is_blank(b))
+    }
+
+    #[inline]
+    fn is_record_term(&self, c: u8) -> bool {
+        self.record_term == c
+    }
+
+    /// Returns true if `c` escapes the next byte of an unquoted field.
+    #[inline]
+    fn is_unquoted_escape(&self, c: u8) -> bool {
+        self.escape == Some(c) && self.escape_mode == EscapeMode::Everywhere
+    }
+
+    /// Returns true if `c` starts a new line inside a quoted field.
+    ///
+    /// The `\n` in a `\r\n` pair is not counted since the `\r` already was.
+    #[inline]
+    fn is_field_line_term(&self, c: u8) -> bool {
+        self.is_record_term(c)
+        && !(self.record_term.is_crlf()
+             && c == b'\n'
+             && self.fieldbuf.last() == Some(&b'\r'))
+    }
+
+    fn parse_error(&self, err: ParseError) -> NextField<[u8]> {
+        NextField::Error(Error::Parse(LocatableError {
+            record: self.irecord,
+            field: self.ifield,
+            err: err,
+        }))
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    StartRecord,
+    EndRecord,
+    InComment,
+    InLeading,
+    StartField,
+    InField,
+    InQuotedField,
+    InEscapedQuote,
+    InDoubleEscapedQuote,
+    InUnquotedEscape,
+}
+
+impl<R: io::Read + io::Seek> Reader<R> {
+    /// Seeks the underlying reader to the file cursor specified.
+    ///
+    /// This comes with several caveats:
+    ///
+    /// * The existing buffer is dropped and a new one is created.
+    /// * If you seek to a position other than the start of a record, you'll
+    ///   probably get an incorrect parse. (This is *not* unsafe.)
+    ///   One exception is seeking into the middle of a `\r\n` record
+    ///   terminator: the orphaned `\n` is skipped just like an empty line.
+    ///
+    /// Mostly, this is intended for use with the `index` sub module.
+    ///
+    /// Note that if `pos` is equivalent to the current *parsed* byte offset,
+    /// then no seeking is performed. (In this case, `seek` is a no-op.)
+    /// Similarly, if `pos` is ahead of the current position but the data at
+    /// `pos` has already been read into the reader's buffer, then the
+    /// parser skips ahead in its buffer without seeking the underlying
+    /// reader.
+    pub fn seek(&mut self, pos: u64) -> Result<()> {
+        self.first_handled = true;
+        self.raw_done = false;
+        self.peeked = None;
+        self.into_field = None;
+        self.skip_lines = 0;
+        self.term_len = 0;
+        self.cr_pending = false;
+        self.state = StartRecord;
+        if pos == self.byte_offset() {
+            return Ok(())
+        }
+        let buffered = (self.buf.len() - self.bufi) as u64;
+        let in_buffer =
+            pos > self.byte_offset && pos - self.byte_offset <= buffered;
+        if in_buffer {
+            // The target is already buffered, so just skip ahead to it.
+            self.bufi += (pos - self.byte_offset) as usize;
+        } else {
+            self.bufi = self.buf.len(); // will force a buffer refresh
+            self.eof = false;
+        }
+        self.bytes_before_seek = self.total_bytes_read();
+        self.seek_offset = pos;
+        self.byte_offset = pos;
+        if !in_buffer {
+            try!(self.rdr.seek(io::SeekFrom::Start(pos)));
+        }
+        Ok(())
+    }
+
+    /// Seeks to the first record boundary at or after `pos`.
+    ///
+    /// This is like `seek`, except that `pos` doesn't need to be at the
+    /// start of a record. The reader scans forward from `pos` to just past
+    /// the next record terminator (unless `pos` already starts a line) and
+    /// returns the byte offset at which parsing will resume. If no record
+    /// terminator follows `pos`, then the offset of the end of the data is
+    /// returned and no more records are read.
+    ///
+    /// Note that this reads extra data to find the boundary. Also, the
+    /// boundary is found by looking for record terminators only, so a
+    /// terminator inside a quoted field is mistaken for the end of a
+    /// record. Quoting rules can't be taken into account without parsing
+    /// from a known record boundary.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use std::io;
+    ///
+    /// let data = "a,b\nfoo,bar\nbaz,quux\n";
+    /// let mut rdr = csv::Reader::from_reader(io::Cursor::new(data))
+    ///                           .has_headers(false);
+    /// assert_eq!(rdr.seek_aligned(6).unwrap(), 12);
+    /// let row = rdr.records().next().unwrap().unwrap();
+    /// assert_eq!(row, vec!["baz", "quux"]);
+    /// ```
+    pub fn seek_aligned(&mut self, pos: u64) -> Result<u64> {
+        if pos == 0 {
+            try!(self.seek(0));
+            return Ok(0);
+        }
+        // Start one byte early, in case `pos` is already at a boundary.
+        try!(self.seek(pos - 1));
+        loop {
+            if let Err(err) = self.fill_buf() {
+                if err.kind() != io::ErrorKind::UnexpectedEof {
+                    return Err(Error::Io(err));
+                }
+            }
+            if self.buf.is_empty() {
+                self.eof = true;
+                return Ok(self.byte_offset);
+            }
+            while self.bufi < self.buf.len() {
+                let c = self.buf[self.bufi];
+                self.bump();
+                if self.is_record_term(c) {
+                    if c == b'\r' && self.record_term.is_crlf()
+                        && self.buf.get(self.bufi) == Some(&b'\n') {
                         self.bump();
-                        if c == self.delimiter {
-                            self.state = StartField;
-                            return self.next_data();
-                        } else if self.is_record_term(c) {
-                            self.bump_eor(c);
-                            self.state = EndRecord;
-                            return self.next_data();
-                        } else {
-                            self.add(c);
-                        }
                     }
-                    InQuotedField => {
-                        self.bump();
-                        if c == self.quote {
-                            self.state = InDoubleEscapedQuote;
-                        } else if self.escape == Some(c) {
-                            self.state = InEscapedQuote;
-                        } else {
-                            self.add(c);
-                        }
+                    return Ok(self.byte_offset);
+                }
+            }
+        }
+    }
+
+    /// Seeks the underlying reader back to the start of the CSV data.
+    ///
+    /// Unlike `seek`, this keeps any previously read headers valid and
+    /// re-arms the header handling of the record iterators. Namely, if
+    /// `has_headers` is enabled, then the header row is skipped again on the
+    /// next pass (and is returned as the first record otherwise). This makes
+    /// it easy to process the same CSV data more than once.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2\n3,4");
+    ///
+    /// let pass1 = rdr.records().collect::<csv::Result<Vec<_>>>().unwrap();
+    /// rdr.seek_to_start().unwrap();
+    /// let pass2 = rdr.records().collect::<csv::Result<Vec<_>>>().unwrap();
+    ///
+    /// assert_eq!(pass1.len(), 2);
+    /// assert_eq!(pass1, pass2);
+    /// assert_eq!(rdr.headers().unwrap(), vec!["a", "b"]);
+    /// ```
+    pub fn seek_to_start(&mut self) -> Result<()> {
+        try!(self.rdr.seek(io::SeekFrom::Start(0)));
+        self.bufi = self.buf.len(); // will force a buffer refresh
+        self.eof = false;
+        self.bytes_before_seek = self.total_bytes_read();
+        self.read_limit_base = self.bytes_before_seek;
+        self.seek_offset = 0;
+        self.byte_offset = 0;
+        self.term_len = 0;
+        self.cr_pending = false;
+        self.state = StartRecord;
+        self.irecord = 1;
+        self.ifield = 0;
+        self.first_handled = false;
+        self.peeked = None;
+        self.into_field = None;
+        self.skip_lines = self.header_line;
+        if !self.first_row.is_empty() {
+            // The first record is already cached, so skip past it. The
+            // record iterators use the cached copy instead of re-reading it.
+            loop {
+                match self.next_bytes() {
+                    NextField::EndOfRecord | NextField::EndOfCsv => break,
+                    NextField::Error(err) => return Err(err),
+                    NextField::Data(_) => {}
+                }
+            }
+        }
+        self.raw_done = false;
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+pub struct UnsafeByteFields<'a, R: 'a> {
+    rdr: &'a mut Reader<R>,
+}
+
+#[doc(hidden)]
+impl<'a, R> Iterator for UnsafeByteFields<'a, R> where R: io::Read {
+    type Item = Result<&'a [u8]>;
+
+    fn next(&mut self) -> Option<Result<&'a [u8]>> {
+        unsafe {
+            ::std::mem::transmute(self.rdr.next_bytes().into_iter_result())
+        }
+    }
+}
+
+/// An iterator of decoded records.
+///
+/// The lifetime parameter `'a` refers to the lifetime of the underlying
+/// CSV reader.
+///
+/// The `R` type parameter refers to the type of the underlying reader.
+///
+/// The `D` type parameter refers to the decoded type.
+pub struct DecodedRecords<'a, R: 'a, D> {
+    p: ByteRecords<'a, R>,
+    _phantom: ::std::marker::PhantomData<D>,
+}
+
+impl<'a, R, D> Iterator for DecodedRecords<'a, R, D>
+        where R: io::Read, D: Decodable {
+    type Item = Result<D>;
+
+    fn next(&mut self) -> Option<Result<D>> {
+        let null = self.p.p.null_value.clone();
+        let record = self.p.next().map(|res| {
+            res.and_then(|byte_record| {
+                let mut d = Decoded::new(byte_record);
+                if let Some(null) = null {
+                    d = d.null_value(null);
+                }
+                d.decode()
+            })
+        });
+        if let Some(Err(_)) = record {
+            self.p.abort_if_asked();
+        }
+        record
+    }
+}
+
+/// An iterator of `String` records that starts with the header row.
+///
+/// The lifetime parameter `'a` refers to the lifetime of the underlying
+/// CSV reader.
+///
+/// The `R` type parameter refers to the type of the underlying reader.
+pub struct RecordsWithHeaders<'a, R: 'a> {
+    p: StringRecords<'a, R>,
+    header: bool,
+}
+
+impl<'a, R> Iterator for RecordsWithHeaders<'a, R> where R: io::Read {
+    type Item = Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Result<Vec<String>>> {
+        if self.header {
+            self.header = false;
+            match self.p.p.p.headers() {
+                // There is no header row when the CSV data is empty.
+                Ok(ref headers) if headers.is_empty() => {}
+                headers => return Some(headers),
+            }
+        }
+        self.p.next()
+    }
+}
+
+/// An iterator of `String` records.
+///
+/// The lifetime parameter `'a` refers to the lifetime of the underlying
+/// CSV reader.
+///
+/// The `R` type parameter refers to the type of the underlying reader.
+pub struct StringRecords<'a, R: 'a> {
+    p: ByteRecords<'a, R>,
+}
+
+impl<'a, R> StringRecords<'a, R> where R: io::Read {
+    /// Collects records up to the first error.
+    ///
+    /// This returns every record read before the first error, along with
+    /// that error (if there was one) and the byte offset at which the
+    /// offending record starts. Records are read lazily, so nothing after
+    /// the first error is read.
+    ///
+    /// This is useful when you want to process as much of the CSV data as
+    /// possible and then report where it went wrong.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut rdr = csv::Reader::from_string("a,b\n1,2\n3\n4,5");
+    /// let (rows, err) = rdr.records().collect_until_error();
+    ///
+    /// assert_eq!(rows, vec![vec!["1".to_string(), "2".to_string()]]);
+    /// assert_eq!(err.unwrap().0, 8);
+    /// ```
+    pub fn collect_until_error(
+        mut self,
+    ) -> (Vec<Vec<String>>, Option<(u64, Error)>) {
+        let mut records = vec![];
+        // Read the header row up front so that the offset of the first
+        // record is correct.
+        if self.p.p.has_headers {
+            if let Err(err) = self.p.p.byte_headers() {
+                return (records, Some((0, err)));
+            }
+        }
+        loop {
+            let start = self.p.p.byte_offset();
+            match self.next() {
+                None => return (records, None),
+                Some(Err(err)) => return (records, Some((start, err))),
+                Some(Ok(record)) => records.push(record),
+            }
+        }
+    }
+}
+
+impl<'a, R> Iterator for StringRecords<'a, R> where R: io::Read {
+    type Item = Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Result<Vec<String>>> {
+        loop {
+            let record = match self.p.next() {
+                None => return None,
+                Some(Err(err)) => return Some(Err(err)),
+                Some(Ok(record)) => record,
+            };
+            let policy = self.p.p.on_error;
+            if policy == Some(OnError::Lossy) {
+                return Some(Ok(byte_record_to_utf8_lossy(record)));
+            }
+            match byte_record_to_utf8(record) {
+                Ok(record) => return Some(Ok(record)),
+                Err(err) => {
+                    if let Some(ref mut sink) = self.p.p.error_sink {
+                        sink(&err);
+                        continue;
                     }
-                    InEscapedQuote => {
-                        self.bump();
-                        self.add(c);
-                        self.state = InQuotedField;
+                    if policy == Some(OnError::Skip) {
+                        continue;
                     }
-                    InDoubleEscapedQuote => {
-                        self.bump();
-                        if self.double_quote && c == self.quote {
-                            self.add(c);
-                            self.state = InQuotedField;
-                        } else if c == self.delimiter {
-                            self.state = StartField;
-                            return self.next_data();
-                        } else if self.is_record_term(c) {
-                            self.bump_eor(c);
-                            self.state = EndRecord;
-                            return self.next_data();
-                        } else {
-                            self.add(c);
-                            self.state = InField; // degrade gracefully?
-                        }
+                    self.p.abort_if_asked();
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// An owning iterator of `String` records.
+///
+/// The `R` type parameter refers to the type of the underlying reader.
+pub struct StringRecordsIntoIter<R> {
+    p: Reader<R>,
+    errored: bool,
+}
+
+impl<R> Iterator for StringRecordsIntoIter<R> where R: io::Read {
+    type Item = Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Result<Vec<String>>> {
+        // Errors are handled just like `records` handles them.
+        let mut records = StringRecords {
+            p: ByteRecords { p: &mut self.p, errored: self.errored },
+        };
+        let record = records.next();
+        self.errored = records.p.errored;
+        record
+    }
+}
+
+/// An iterator of `String` records without adjacent duplicates.
+///
+/// The lifetime parameter `'a` refers to the lifetime of the underlying
+/// CSV reader.
+///
+/// The `R` type parameter refers to the type of the underlying reader.
+pub struct DedupRecords<'a, R: 'a> {
+    p: StringRecords<'a, R>,
+    prev: Option<Vec<String>>,
+}
+
+impl<'a, R> Iterator for DedupRecords<'a, R> where R: io::Read {
+    type Item = Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Result<Vec<String>>> {
+        loop {
+            let record = match self.p.next() {
+                None => return None,
+                Some(Err(err)) => return Some(Err(err)),
+                Some(Ok(record)) => record,
+            };
+            if self.prev.as_ref() != Some(&record) {
+                self.prev = Some(record.clone());
+                return Some(Ok(record));
+            }
+        }
+    }
+}
+
+/// An iterator of groups of consecutive `String` records with equal keys.
+///
+/// The lifetime parameter `'a` refers to the lifetime of the underlying
+/// CSV reader.
+///
+/// The `R` type parameter refers to the type of the underlying reader, `K`
+/// to the type of the keys and `F` to the type of the key function.
+pub struct GroupBy<'a, R: 'a, K, F> {
+    p: StringRecords<'a, R>,
+    key: F,
+    group: Option<(K, Vec<Vec<String>>)>,
+}
+
+impl<'a, R, K, F> Iterator for GroupBy<'a, R, K, F>
+        where R: io::Read, K: Eq, F: FnMut(&[String]) -> K {
+    type Item = Result<(K, Vec<Vec<String>>)>;
+
+    fn next(&mut self) -> Option<Result<(K, Vec<Vec<String>>)>> {
+        loop {
+            let record = match self.p.next() {
+                None => return self.group.take().map(Ok),
+                Some(Err(err)) => return Some(Err(err)),
+                Some(Ok(record)) => record,
+            };
+            let key = (self.key)(&record);
+            let same = match self.group {
+                Some((ref k, _)) => *k == key,
+                None => true,
+            };
+            if same {
+                match self.group {
+                    Some((_, ref mut records)) => records.push(record),
+                    None => self.group = Some((key, vec![record])),
+                }
+            } else {
+                let done = self.group.take();
+                self.group = Some((key, vec![record]));
+                return done.map(Ok);
+            }
+        }
+    }
+}
+
+/// An iterator of overlapping pairs of consecutive `String` records.
+///
+/// The lifetime parameter `'a` refers to the lifetime of the underlying
+/// CSV reader.
+///
+/// The `R` type parameter refers to the type of the underlying reader.
+pub struct Windows2<'a, R: 'a> {
+    p: StringRecords<'a, R>,
+    prev: Option<Vec<String>>,
+}
+
+impl<'a, R> Iterator for Windows2<'a, R> where R: io::Read {
+    type Item = Result<(Vec<String>, Vec<String>)>;
+
+    fn next(&mut self) -> Option<Result<(Vec<String>, Vec<String>)>> {
+        loop {
+            let record = match self.p.next() {
+                None => return None,
+                Some(Err(err)) => return Some(Err(err)),
+                Some(Ok(record)) => record,
+            };
+            match self.prev.take() {
+                None => self.prev = Some(record),
+                Some(prev) => {
+                    self.prev = Some(record.clone());
+                    return Some(Ok((prev, record)));
+                }
+            }
+        }
+    }
+}
+
+/// An iterator of `String` records without consecutive duplicates in one
+/// column.
+///
+/// The lifetime parameter `'a` refers to the lifetime of the underlying
+/// CSV reader.
+///
+/// The `R` type parameter refers to the type of the underlying reader.
+pub struct DedupRecordsBy<'a, R: 'a> {
+    p: StringRecords<'a, R>,
+    col: usize,
+    // The key of the previous record. This buffer is reused.
+    prev: String,
+    has_prev: bool,
+}
+
+impl<'a, R> Iterator for DedupRecordsBy<'a, R> where R: io::Read {
+    type Item = Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Result<Vec<String>>> {
+        loop {
+            let record = match self.p.next() {
+                None => return None,
+                Some(Err(err)) => return Some(Err(err)),
+                Some(Ok(record)) => record,
+            };
+            match record.get(self.col) {
+                None => self.has_prev = false,
+                Some(key) => {
+                    if self.has_prev && self.prev == *key {
+                        continue;
                     }
+                    self.prev.clear();
+                    self.prev.push_str(key);
+                    self.has_prev = true;
                 }
             }
+            return Some(Ok(record));
         }
     }
+}
 
-    /// This is just like `next_bytes` except it converts each field to
-    /// a Unicode string in place.
-    pub fn next_str(&mut self) -> NextField<str> {
-        // This really grates me. Once we call `next_bytes`, we initiate a
-        // *mutable* borrow of `self` that doesn't stop until the return value
-        // goes out of scope. Since we have to return that value, it will never
-        // go out of scope in this function.
-        //
-        // Therefore, we can't get access to any state information after
-        // calling `next_bytes`. But we might need it to report an error.
-        //
-        // One possible way around this is to use interior mutability...
-        let (record, field) = (self.irecord, self.ifield);
-        match self.next_bytes() {
-            NextField::EndOfRecord => NextField::EndOfRecord,
-            NextField::EndOfCsv => NextField::EndOfCsv,
-            NextField::Error(err) => NextField::Error(err),
-            NextField::Data(bytes) => {
-                match str::from_utf8(bytes) {
-                    Ok(s) => NextField::Data(s),
-                    Err(_) => NextField::Error(Error::Parse(LocatableError {
-                        record: record,
-                        field: field,
-                        err: ParseError::InvalidUtf8,
-                    })),
+/// An iterator of `String` records that stops at the first record that
+/// doesn't satisfy a predicate.
+///
+/// This is created by `Reader::take_records_while`.
+///
+/// The lifetime parameter `'a` refers to the lifetime of the underlying
+/// CSV reader.
+///
+/// The `R` type parameter refers to the type of the underlying reader.
+pub struct TakeRecordsWhile<'a, R: 'a, P> {
+    p: StringRecords<'a, R>,
+    pred: P,
+    done: bool,
+}
+
+impl<'a, R, P> Iterator for TakeRecordsWhile<'a, R, P>
+        where R: io::Read, P: FnMut(&[String]) -> bool {
+    type Item = Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Result<Vec<String>>> {
+        if self.done {
+            return None;
+        }
+        let record = match self.p.next() {
+            None => return None,
+            Some(Err(err)) => return Some(Err(err)),
+            Some(Ok(record)) => record,
+        };
+        if (self.pred)(&record) {
+            Some(Ok(record))
+        } else {
+            self.p.p.p.put_back(record);
+            self.done = true;
+            None
+        }
+    }
+}
+
+/// A cursor over `String` records with a bounded lookahead window.
+///
+/// This is created by `Reader::with_lookahead`.
+///
+/// The lifetime parameter `'a` refers to the lifetime of the underlying
+/// CSV reader.
+///
+/// The `R` type parameter refers to the type of the underlying reader.
+pub struct LookaheadReader<'a, R: 'a> {
+    p: StringRecords<'a, R>,
+    n: usize,
+    // The current record followed by up to `n` upcoming records.
+    buf: VecDeque<Vec<String>>,
+    started: bool,
+    done: bool,
+    // An error found while reading ahead. It is returned once the cursor
+    // reaches it.
+    err: Option<Error>,
+}
+
+impl<'a, R> LookaheadReader<'a, R> where R: io::Read {
+    /// Moves the cursor to the next record.
+    ///
+    /// This returns `false` once there are no more records. If an error
+    /// occurred while reading the next record, then it is returned here
+    /// (and not before, even if it was found while reading ahead).
+    pub fn advance(&mut self) -> Result<bool> {
+        if self.started {
+            self.buf.pop_front();
+        }
+        self.started = true;
+        self.fill();
+        if !self.buf.is_empty() {
+            return Ok(true);
+        }
+        match self.err.take() {
+            None => Ok(false),
+            Some(err) => Err(err),
+        }
+    }
+
+    /// Returns the current record.
+    ///
+    /// This returns `None` before the first call to `advance` and after
+    /// the last record.
+    pub fn current(&self) -> Option<&[String]> {
+        if !self.started {
+            return None;
+        }
+        self.buf.front().map(|r| &**r)
+    }
+
+    /// Returns the `k`th record after the current one, starting at `0`.
+    ///
+    /// This returns `None` if `k` is not less than the size of the
+    /// lookahead window, or if there is no such record. A record that
+    /// failed to parse can't be peeked at either.
+    pub fn peek(&self, k: usize) -> Option<&[String]> {
+        if !self.started || k >= self.n {
+            return None;
+        }
+        self.buf.get(k + 1).map(|r| &**r)
+    }
+
+    fn fill(&mut self) {
+        while !self.done && self.err.is_none() && self.buf.len() <= self.n {
+            match self.p.next() {
+                None => self.done = true,
+                Some(Err(err)) => self.err = Some(err),
+                Some(Ok(record)) => self.buf.push_back(record),
+            }
+        }
+    }
+}
+
+/// An iterator of `ByteString` records.
+///
+/// The lifetime parameter `'a` refers to the lifetime of the underlying
+/// CSV reader.
+///
+/// The `R` type parameter refers to the type of the underlying reader.
+pub struct ByteRecords<'a, R: 'a> {
+    p: &'a mut Reader<R>,
+    errored: bool,
+}
+
+impl<'a, R> Iterator for ByteRecords<'a, R> where R: io::Read {
+    type Item = Result<Vec<ByteString>>;
+
+    fn next(&mut self) -> Option<Result<Vec<ByteString>>> {
+        if self.errored {
+            return None;
+        }
+        loop {
+            let record = self.p.next_byte_record();
+            if let Some(Err(ref err)) = record {
+                if self.p.skip_error(err) {
+                    continue;
                 }
+                self.errored = true;
             }
+            return record;
         }
     }
+}
 
-    /// An unsafe iterator over byte fields.
-    ///
-    /// This iterator calls `next_bytes` at each step.
-    ///
-    /// It is (wildly) unsafe because the lifetime yielded for each element
-    /// is incorrect. It refers to the lifetime of the CSV reader instead of
-    /// the lifetime of the internal buffer. Which means you can `collect`
-    /// it into a vector and obliterate memory safety.
-    ///
-    /// The reason it exists is because it appears extremely difficult to write
-    /// a fast streaming iterator. (But iterators are wildly convenient.)
-    #[doc(hidden)]
-    pub unsafe fn byte_fields<'a>(&'a mut self) -> UnsafeByteFields<'a, R> {
-        UnsafeByteFields { rdr: self }
+impl<'a, R> ByteRecords<'a, R> where R: io::Read {
+    /// Stops iteration if the `OnError::Abort` policy is set.
+    fn abort_if_asked(&mut self) {
+        if self.p.on_error == Some(OnError::Abort) {
+            self.errored = true;
+        }
     }
+}
 
-    /// Returns the byte offset at which the current record started.
-    pub fn byte_offset(&self) -> u64 {
-        self.byte_offset
+/// An iterator of records that are validated as UTF-8 lazily.
+///
+/// The lifetime parameter `'a` refers to the lifetime of the underlying
+/// CSV reader.
+///
+/// The `R` type parameter refers to the type of the underlying reader.
+pub struct LazyRecords<'a, R: 'a> {
+    p: ByteRecords<'a, R>,
+}
+
+impl<'a, R> Iterator for LazyRecords<'a, R> where R: io::Read {
+    type Item = Result<LazyStringRecord>;
+
+    fn next(&mut self) -> Option<Result<LazyStringRecord>> {
+        self.p.next().map(|record| record.map(LazyStringRecord::new))
     }
+}
 
-    #[inline]
-    fn next_data(&mut self) -> NextField<[u8]> {
-        if !self.first_row_done {
-            self.first_row.push(self.fieldbuf.to_vec());
-        }
-        self.ifield += 1;
-        NextField::Data(&self.fieldbuf)
+/// A record whose fields are validated as UTF-8 only when accessed.
+///
+/// The result of validating each field is cached, so every field is
+/// validated at most once.
+///
+/// This is yielded by `Reader::lazy_records`.
+#[derive(Clone, Debug)]
+pub struct LazyStringRecord {
+    fields: Vec<ByteString>,
+    valid: Vec<Cell<Option<bool>>>,
+}
+
+impl LazyStringRecord {
+    fn new(fields: Vec<ByteString>) -> LazyStringRecord {
+        let valid = vec![Cell::new(None); fields.len()];
+        LazyStringRecord { fields: fields, valid: valid }
     }
 
-    #[inline]
-    fn next_eor(&mut self) -> NextField<[u8]> {
-        if !self.flexible
-                && self.first_row_done
-                && self.ifield != self.first_row.len() as u64 {
-            return self.parse_error(ParseError::UnequalLengths {
-                expected: self.first_row.len() as u64,
-                got: self.ifield as u64,
-            });
-        }
-        self.irecord += 1;
-        self.ifield = 0;
-        self.first_row_done = true;
-        NextField::EndOfRecord
+    /// Returns the number of fields in this record.
+    pub fn len(&self) -> usize {
+        self.fields.len()
     }
 
-    #[inline]
-    fn next_eoc(&self) -> NextField<[u8]> {
-        NextField::EndOfCsv
+    /// Returns true if this record has no fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
     }
 
-    #[inline]
-    fn fill_buf(&mut self) -> io::Result<()> {
-        if self.bufi == self.buf.len() {
-            unsafe { let cap = self.buf.capacity(); self.buf.set_len(cap); }
-            let n = try!(self.rdr.read(&mut self.buf));
-            unsafe { self.buf.set_len(n); }
-            self.bufi = 0;
+    /// Returns the field at index `i` as a string.
+    ///
+    /// If there is no such field, then `None` is returned. If the field is
+    /// not valid UTF-8, then an error is returned.
+    pub fn get(&self, i: usize) -> Option<Result<&str>> {
+        if i >= self.fields.len() {
+            return None;
+        }
+        let bytes = &self.fields[i];
+        let valid = match self.valid[i].get() {
+            Some(valid) => valid,
+            None => {
+                let valid = str::from_utf8(bytes).is_ok();
+                self.valid[i].set(Some(valid));
+                valid
+            }
+        };
+        if valid {
+            Some(Ok(unsafe { str::from_utf8_unchecked(bytes) }))
+        } else {
+            let err = str::from_utf8(bytes).unwrap_err();
+            Some(Err(Error::Decode(format!(
+                "Could not decode the following bytes as UTF-8 \
+                 because {}: {:?}", err, bytes))))
         }
-        Ok(())
     }
 
-    #[inline]
-    fn bump(&mut self) {
-        self.bufi += 1;
-        self.byte_offset += 1;
+    /// Returns the field at index `i` as raw bytes.
+    pub fn get_bytes(&self, i: usize) -> Option<&[u8]> {
+        self.fields.get(i).map(|bytes| &**bytes)
     }
 
-    #[inline]
-    fn bump_eor(&mut self, c: u8) {
-        if !self.record_term.is_crlf() || c != b'\r' {
-            return;
+    /// Returns the fields of this record as raw bytes.
+    pub fn into_bytes(self) -> Vec<ByteString> {
+        self.fields
+    }
+}
+
+/// A reader of `String` records that share a single reusable buffer.
+///
+/// This cannot implement `Iterator` since every batch borrows from the
+/// buffer that the next batch overwrites. Use `next_batch` in a `while let`
+/// loop instead.
+///
+/// The lifetime parameter `'a` refers to the lifetime of the underlying
+/// CSV reader.
+///
+/// The `R` type parameter refers to the type of the underlying reader.
+pub struct ArenaRecords<'a, R: 'a> {
+    p: &'a mut Reader<R>,
+    errored: bool,
+    err: Option<Error>,
+    batch_size: usize,
+    buf: Vec<u8>,
+    field_ends: Vec<usize>,
+    record_ends: Vec<usize>,
+}
+
+impl<'a, R> ArenaRecords<'a, R> where R: io::Read {
+    /// Reads the next batch of records, resetting the shared buffer.
+    ///
+    /// Every batch contains at least one record. `None` is returned once
+    /// the CSV data has been exhausted.
+    ///
+    /// If an error occurs part way through a batch, then the records read
+    /// before the error are returned first and the error is returned on the
+    /// subsequent call. As with `records`, a record that is not valid UTF-8
+    /// is reported as an error and reading continues after it.
+    pub fn next_batch<'b>(&'b mut self) -> Option<Result<RecordBatch<'b>>> {
+        if let Some(err) = self.err.take() {
+            return Some(Err(err));
+        }
+        self.buf.clear();
+        self.field_ends.clear();
+        self.record_ends.clear();
+        match self.p.pending_record() {
+            None => {}
+            Some(Err(err)) => {
+                self.errored = true;
+                return Some(Err(err));
+            }
+            Some(Ok(record)) => {
+                for field in &record {
+                    self.buf.extend_from_slice(field);
+                    self.field_ends.push(self.buf.len());
+                }
+                self.end_record();
+            }
+        }
+        while self.record_ends.len() < self.batch_size
+                && self.err.is_none()
+                && !self.p.done()
+                && !self.errored {
+            let start = self.field_ends.len();
+            loop {
+                match self.p.next_bytes() {
+                    NextField::EndOfRecord | NextField::EndOfCsv => break,
+                    NextField::Error(err) => {
+                        self.errored = true;
+                        self.err = Some(err);
+                        break;
+                    }
+                    NextField::Data(field) => {
+                        self.buf.extend_from_slice(field);
+                        self.field_ends.push(self.buf.len());
+                    }
+                }
+            }
+            if self.errored {
+                self.truncate_to(start);
+            } else if self.field_ends.len() > start {
+                self.end_record();
+            }
         }
-        if self.buf.get(self.bufi) == Some(&b'\n') {
-            self.bump();
+        if self.record_ends.is_empty() {
+            return self.err.take().map(Err);
         }
+        Some(Ok(RecordBatch {
+            buf: unsafe { str::from_utf8_unchecked(&self.buf) },
+            field_ends: &self.field_ends,
+            record_ends: &self.record_ends,
+        }))
     }
 
-    #[inline]
-    fn add(&mut self, c: u8) {
-        self.fieldbuf.push(c);
+    /// Finishes the record at the end of the buffer, dropping it and
+    /// remembering an error if any of its fields is not valid UTF-8.
+    ///
+    /// Each field is checked on its own since fields are sliced out of the
+    /// buffer separately, and a character may not straddle two fields.
+    fn end_record(&mut self) {
+        let start = self.record_ends.last().cloned().unwrap_or(0);
+        for i in start..self.field_ends.len() {
+            let field = &self.buf[self.field_start(i)..self.field_ends[i]];
+            if let Err(err) = str::from_utf8(field) {
+                self.err = Some(Error::Decode(format!(
+                    "Could not decode the following bytes as UTF-8 \
+                     because {}: {:?}", err, field)));
+                break;
+            }
+        }
+        if self.err.is_some() {
+            self.truncate_to(start);
+        } else {
+            self.record_ends.push(self.field_ends.len());
+        }
     }
 
-    #[inline]
-    fn is_record_term(&self, c: u8) -> bool {
-        self.record_term == c
+    fn truncate_to(&mut self, field: usize) {
+        let bstart = self.field_start(field);
+        self.buf.truncate(bstart);
+        self.field_ends.truncate(field);
     }
 
-    fn parse_error(&self, err: ParseError) -> NextField<[u8]> {
-        NextField::Error(Error::Parse(LocatableError {
-            record: self.irecord,
-            field: self.ifield,
-            err: err,
-        }))
+    fn field_start(&self, field: usize) -> usize {
+        if field == 0 { 0 } else { self.field_ends[field - 1] }
     }
 }
 
-#[derive(Debug)]
-enum State {
-    StartRecord,
-    EndRecord,
-    StartField,
-    InField,
-    InQuotedField,
-    InEscapedQuote,
-    InDoubleEscapedQuote,
+/// A batch of `String` records borrowed from an `ArenaRecords` buffer.
+///
+/// The lifetime parameter `'b` refers to the lifetime of the batch, which
+/// ends at the next call to `next_batch`.
+#[derive(Clone, Copy, Debug)]
+pub struct RecordBatch<'b> {
+    buf: &'b str,
+    field_ends: &'b [usize],
+    record_ends: &'b [usize],
 }
 
-impl<R: io::Read + io::Seek> Reader<R> {
-    /// Seeks the underlying reader to the file cursor specified.
-    ///
-    /// This comes with several caveats:
-    ///
-    /// * The existing buffer is dropped and a new one is created.
-    /// * If you seek to a position other than the start of a record, you'll
-    ///   probably get an incorrect parse. (This is *not* unsafe.)
-    ///
-    /// Mostly, this is intended for use with the `index` sub module.
-    ///
-    /// Note that if `pos` is equivalent to the current *parsed* byte offset,
-    /// then no seeking is performed. (In this case, `seek` is a no-op.)
-    pub fn seek(&mut self, pos: u64) -> Result<()> {
-        self.has_seeked = true;
-        self.state = StartRecord;
-        if pos == self.byte_offset() {
-            return Ok(())
-        }
-        self.bufi = self.buf.len(); // will force a buffer refresh
-        self.eof = false;
-        self.byte_offset = pos;
-        try!(self.rdr.seek(io::SeekFrom::Start(pos)));
-        Ok(())
+impl<'b> RecordBatch<'b> {
+    /// Returns the number of records in this batch.
+    pub fn len(&self) -> usize {
+        self.record_ends.len()
+    }
+
+    /// Returns true if and only if this batch contains no records.
+    pub fn is_empty(&self) -> bool {
+        self.record_ends.is_empty()
+    }
+
+    /// Returns the `i`th record in this batch, if it exists.
+    pub fn get(&self, i: usize) -> Option<ArenaRecord<'b>> {
+        let end = match self.record_ends.get(i) {
+            None => return None,
+            Some(&end) => end,
+        };
+        let start = if i == 0 { 0 } else { self.record_ends[i - 1] };
+        let bstart = if start == 0 { 0 } else { self.field_ends[start - 1] };
+        Some(ArenaRecord {
+            buf: self.buf,
+            start: bstart,
+            ends: &self.field_ends[start..end],
+        })
+    }
+
+    /// Returns an iterator over the records in this batch.
+    pub fn iter(&self) -> RecordBatchIter<'b> {
+        RecordBatchIter { batch: *self, i: 0 }
     }
 }
 
-#[doc(hidden)]
-pub struct UnsafeByteFields<'a, R: 'a> {
-    rdr: &'a mut Reader<R>,
+/// An iterator over the records in a `RecordBatch`.
+pub struct RecordBatchIter<'b> {
+    batch: RecordBatch<'b>,
+    i: usize,
 }
 
-#[doc(hidden)]
-impl<'a, R> Iterator for UnsafeByteFields<'a, R> where R: io::Read {
-    type Item = Result<&'a [u8]>;
+impl<'b> Iterator for RecordBatchIter<'b> {
+    type Item = ArenaRecord<'b>;
 
-    fn next(&mut self) -> Option<Result<&'a [u8]>> {
-        unsafe {
-            ::std::mem::transmute(self.rdr.next_bytes().into_iter_result())
+    fn next(&mut self) -> Option<ArenaRecord<'b>> {
+        let record = self.batch.get(self.i);
+        if record.is_some() {
+            self.i += 1;
         }
+        record
     }
 }
 
-/// An iterator of decoded records.
-///
-/// The lifetime parameter `'a` refers to the lifetime of the underlying
-/// CSV reader.
-///
-/// The `R` type parameter refers to the type of the underlying reader.
-///
-/// The `D` type parameter refers to the decoded type.
-pub struct DecodedRecords<'a, R: 'a, D> {
-    p: ByteRecords<'a, R>,
-    _phantom: ::std::marker::PhantomData<D>,
+/// A single record borrowed from a `RecordBatch`.
+#[derive(Clone, Copy, Debug)]
+pub struct ArenaRecord<'b> {
+    buf: &'b str,
+    start: usize,
+    ends: &'b [usize],
 }
 
-impl<'a, R, D> Iterator for DecodedRecords<'a, R, D>
-        where R: io::Read, D: Decodable {
-    type Item = Result<D>;
+impl<'b> ArenaRecord<'b> {
+    /// Returns the number of fields in this record.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
 
-    fn next(&mut self) -> Option<Result<D>> {
-        self.p.next().map(|res| {
-            res.and_then(|byte_record| {
-                Decodable::decode(&mut Decoded::new(byte_record))
-            })
-        })
+    /// Returns true if and only if this record has no fields.
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Returns the `i`th field in this record, if it exists.
+    pub fn get(&self, i: usize) -> Option<&'b str> {
+        let end = match self.ends.get(i) {
+            None => return None,
+            Some(&end) => end,
+        };
+        let start = if i == 0 { self.start } else { self.ends[i - 1] };
+        Some(&self.buf[start..end])
+    }
+
+    /// Returns an iterator over the fields in this record.
+    pub fn iter(&self) -> ArenaFields<'b> {
+        ArenaFields { record: *self, i: 0 }
+    }
+
+    /// Copies the fields of this record into an owned record.
+    pub fn to_vec(&self) -> Vec<String> {
+        self.iter().map(|f| f.to_owned()).collect()
     }
 }
 
-/// An iterator of `String` records.
-///
-/// The lifetime parameter `'a` refers to the lifetime of the underlying
-/// CSV reader.
-///
-/// The `R` type parameter refers to the type of the underlying reader.
-pub struct StringRecords<'a, R: 'a> {
-    p: ByteRecords<'a, R>,
+/// An iterator over the fields in an `ArenaRecord`.
+pub struct ArenaFields<'b> {
+    record: ArenaRecord<'b>,
+    i: usize,
 }
 
-impl<'a, R> Iterator for StringRecords<'a, R> where R: io::Read {
-    type Item = Result<Vec<String>>;
+impl<'b> Iterator for ArenaFields<'b> {
+    type Item = &'b str;
 
-    fn next(&mut self) -> Option<Result<Vec<String>>> {
-        self.p.next().map(|res| {
-            res.and_then(|byte_record| {
-                byte_record_to_utf8(byte_record)
-            })
-        })
+    fn next(&mut self) -> Option<&'b str> {
+        let field = self.record.get(self.i);
+        if field.is_some() {
+            self.i += 1;
+        }
+        field
     }
 }
 
-/// An iterator of `ByteString` records.
-///
-/// The lifetime parameter `'a` refers to the lifetime of the underlying
-/// CSV reader.
+/// A fixed-size array of `String` fields, as read by
+/// `Reader::read_record_array`.
 ///
-/// The `R` type parameter refers to the type of the underlying reader.
-pub struct ByteRecords<'a, R: 'a> {
-    p: &'a mut Reader<R>,
-    first: bool,
-    errored: bool,
+/// This is implemented for arrays of `0` up to `32` fields.
+pub trait RecordArray: Sized {
+    /// Returns the number of fields in the array.
+    fn len() -> usize;
+
+    /// Builds the array from a record with exactly `len()` fields.
+    fn from_record(record: Vec<String>) -> Self;
 }
 
-impl<'a, R> Iterator for ByteRecords<'a, R> where R: io::Read {
-    type Item = Result<Vec<ByteString>>;
+macro_rules! record_array_impls {
+    ($($n:expr)*) => {
+        $(
+            impl RecordArray for [String; $n] {
+                fn len() -> usize { $n }
 
-    fn next(&mut self) -> Option<Result<Vec<ByteString>>> {
-        // We check this before checking `done` because the parser could
-        // be done after a call to `byte_headers` but before any iterator
-        // traversal. Once we start the iterator, we must allow the first
-        // row to be returned if the caller has said that this CSV data
-        // has no headers.
-        if !self.first {
-            // Never do this special first record processing again.
-            self.first = true;
-
-            // Always consume the header record. This let's us choose to
-            // return it or ignore it and move on to the next record.
-            // If headers have been read before this point, then this is
-            // equivalent to a harmless clone (and no parser progression).
-            let headers = self.p.byte_headers();
-
-            // If the header row is empty, then the CSV data contains
-            // no records. Never return zero-length records!
-            if headers.as_ref().map(|r| r.is_empty()).unwrap_or(false) {
-                assert!(self.p.done());
-                return None;
-            }
-
-            // This is important. If the client says this CSV data has no
-            // headers but calls `headers` before iterating records (which is
-            // perfectly valid), then we need to make sure to return that
-            // first record.
-            //
-            // If the client says the CSV data has headers, then the first
-            // record should always be ignored.
-            if !self.p.has_headers {
-                return Some(headers);
-            }
-        }
-        // OK, we're done checking the weird first-record-corner-case.
-        if self.p.done() || self.errored {
-            return None;
-        }
-        let mut record = Vec::with_capacity(self.p.first_row.len());
-        loop {
-            match self.p.next_bytes() {
-                NextField::EndOfRecord | NextField::EndOfCsv => {
-                    if record.len() == 0 {
-                        return None
+                fn from_record(record: Vec<String>) -> [String; $n] {
+                    let mut fields: [String; $n] = Default::default();
+                    for (slot, field) in fields.iter_mut().zip(record) {
+                        *slot = field;
                     }
-                    break
-                }
-                NextField::Error(err) => {
-                    self.errored = true;
-                    return Some(Err(err));
+                    fields
                 }
-                NextField::Data(field) => record.push(field.to_vec()),
             }
+        )*
+    }
+}
+
+record_array_impls! {
+    0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16
+    17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+}
+
+/// Guesses whether `sample` is delimited by `delimiter`.
+///
+/// This returns `true` if more than half of the non-empty lines in `sample`
+/// contain `delimiter`. Quoting is ignored, so a delimiter inside a quoted
+/// field counts too. An empty sample isn't delimited.
+///
+/// This is meant for telling delimited data apart from fixed-width data,
+/// which aligns its columns with padding instead. If this returns `false`,
+/// then `sample` should be read with a fixed-width parser, which this crate
+/// doesn't provide. See also `Reader::detect_headers`.
+///
+/// ### Example
+///
+/// ```rust
+/// assert!(csv::looks_delimited(b"a,b\n1,2\n3,4\n", b','));
+/// assert!(!csv::looks_delimited(b"a    b\n1    2\n3    4\n", b','));
+/// ```
+pub fn looks_delimited(sample: &[u8], delimiter: u8) -> bool {
+    let (mut lines, mut delimited) = (0, 0);
+    for line in sample.split(|&b| b == b'\n' || b == b'\r') {
+        if line.is_empty() {
+            continue;
+        }
+        lines += 1;
+        if line.contains(&delimiter) {
+            delimited += 1;
         }
-        Some(Ok(record))
     }
+    delimited * 2 > lines
+}
+
+/// Returns true if `b` is ASCII whitespace other than a line terminator.
+fn is_blank(b: u8) -> bool {
+    b == b' ' || b == b'\t' || b == b'\x0B' || b == b'\x0C'
+}
+
+/// Returns true if `field` looks like a number.
+fn is_numeric(field: &[u8]) -> bool {
+    let s = match str::from_utf8(field) {
+        Err(_) => return false,
+        Ok(s) => s.trim(),
+    };
+    // This excludes words that parse as floats, like `inf` or `NaN`.
+    s.bytes().any(|b| b"0123456789".contains(&b)) && s.parse::<f64>().is_ok()
+}
+
+fn byte_record_to_utf8_lossy(record: Vec<ByteString>) -> Vec<String> {
+    record.into_iter().map(|bytes| {
+        match String::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(err) => {
+                String::from_utf8_lossy(&err.into_bytes()).into_owned()
+            }
+        }
+    }).collect()
 }
 
 fn byte_record_to_utf8(record: Vec<ByteString>) -> Result<Vec<String>> {
@@ -1,7 +1,8 @@
 use std::io::{self, Read, Seek, Write};
 use {
-    Reader, Writer, ByteString, Result,
-    RecordTerminator, QuoteStyle,
+    Reader, Writer, ByteString, Result, Error, LocatableError, ParseError,
+    RecordTerminator, QuoteStyle, EscapeMode, OnError, FloatFormat,
+    ReaderStats, looks_delimited, ColumnType, ColumnKind, ColumnSource,
 };
 
 fn assert_svec_eq<S, T>(got: Vec<Vec<S>>, expected: Vec<Vec<T>>)
@@ -112,9 +113,12 @@ macro_rules! fail_writes_as {
 
 macro_rules! encodes_as {
     ($name:ident, $vec:expr, $csv:expr) => (
+        encodes_as!($name, $vec, $csv, |wtr| wtr);
+    );
+    ($name:ident, $vec:expr, $csv:expr, $config:expr) => (
         #[test]
         fn $name() {
-            let mut wtr = Writer::from_memory();
+            let mut wtr = $config(Writer::from_memory());
             for row in $vec.into_iter() {
                 wtr.encode(row).unwrap();
             }
@@ -254,6 +258,11 @@ decodes_to!(decode_val, "false,-5,5", (Val, Val, Val),
             vec![(Val::Bool(false), Val::Signed(-5), Val::Unsigned(5))]);
 decodes_to!(decode_opt_val, "1.0", (Option<Val>,), vec![(None,)]);
 
+decodes_to!(decode_opt_fallback, "a,b", (Option<usize>, String),
+            vec![(None, "b".into())]);
+decodes_to!(decode_nested_tuple, "1,2,3", ((usize, usize), usize),
+            vec![((1usize, 2usize), 3usize)]);
+
 decodes_to!(decode_tail, "abc,1,2,3,4", (String, Vec<usize>),
             vec![("abc".into(), vec![1usize, 2, 3, 4])]);
 
@@ -319,6 +328,19 @@ fail_writes_as!(wtr_never_quote_needs_quotes, vec![vec![","]], "\",\"",
 encodes_as!(encode_int, vec![(1usize,)], "1\n");
 encodes_as!(encode_many_int, vec![(1usize, 2i16)], "1,2\n");
 encodes_as!(encode_float, vec![(1f64, 1.0f64, 1.5f64)], "1.0,1.0,1.5\n");
+encodes_as!(encode_float_big, vec![(1e7f64, 1.5e-3f64)],
+            "10000000.0,0.0015\n");
+encodes_as!(encode_float_fixed, vec![("x", 7u64, 1e7f64, 2.5f32, -0.1f64)],
+            "x,7,10000000.00,2.50,-0.10\n",
+            |wtr: Writer<_>| wtr.float_format(FloatFormat::Fixed(2)));
+encodes_as!(encode_float_fixed_zero, vec![(1.75f64,)], "2\n",
+            |wtr: Writer<_>| wtr.float_format(FloatFormat::Fixed(0)));
+encodes_as!(encode_float_shortest, vec![(1.0f64, 0.1f64, 1e7f64)],
+            "1,0.1,10000000\n",
+            |wtr: Writer<_>| wtr.float_format(FloatFormat::Shortest));
+encodes_as!(encode_float_shortest_f32, vec![(0.1f32, 1.5f32, 3e-7f32)],
+            "0.1,1.5,0.0000003\n",
+            |wtr: Writer<_>| wtr.float_format(FloatFormat::Shortest));
 encodes_as!(encode_char, vec![('a',)], "a\n");
 encodes_as!(encode_none, vec![(None::<bool>,)], "\"\"\n");
 encodes_as!(encode_some, vec![(Some(true),)], "true\n");
@@ -349,6 +371,40 @@ fn no_headers_no_skip() {
     assert_eq!(rows.len(), 2);
 }
 
+#[test]
+fn no_headers_headers_then_records() {
+    let mut d = Reader::from_string("a,b\nc,d").has_headers(false);
+    assert_eq!(d.headers().unwrap(), vec!["a", "b"]);
+    let rows = d.records().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["c", "d"]]);
+    assert_eq!(d.headers().unwrap(), vec!["a", "b"]);
+}
+
+#[test]
+fn no_headers_records_then_headers() {
+    let mut d = Reader::from_string("a,b\nc,d").has_headers(false);
+    let rows = d.records().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["c", "d"]]);
+    assert_eq!(d.headers().unwrap(), vec!["a", "b"]);
+}
+
+#[test]
+fn no_headers_headers_between_records() {
+    let mut d = Reader::from_string("a,b\nc,d\ne,f").has_headers(false);
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["a", "b"]);
+    assert_eq!(d.headers().unwrap(), vec!["a", "b"]);
+    let rows = d.byte_records().collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(rows, vec![vec![b"c".to_vec(), b"d".to_vec()],
+                          vec![b"e".to_vec(), b"f".to_vec()]]);
+}
+
+#[test]
+fn no_headers_byte_headers_then_byte_records() {
+    let mut d = Reader::from_string("a,b\nc,d").has_headers(false);
+    assert_eq!(d.byte_headers().unwrap(), vec![b"a".to_vec(), b"b".to_vec()]);
+    assert_eq!(d.byte_records().count(), 2);
+}
+
 #[test]
 fn headers_trailing_lf() {
     let mut d = Reader::from_string("a,b,c\n\n\n\n");
@@ -461,3 +517,2465 @@ fn scratch() {
         let _ = writeln!(&mut io::stderr(), "{:?}", row);
     }
 }
+
+fn arena_rows<R: io::Read>(rdr: &mut Reader<R>, n: usize)
+                          -> Vec<Result<Vec<Vec<String>>>> {
+    let mut batches = rdr.records_arena(n);
+    let mut rows = vec![];
+    while let Some(batch) = batches.next_batch() {
+        rows.push(batch.map(|b| b.iter().map(|r| r.to_vec()).collect()));
+    }
+    rows
+}
+
+#[test]
+fn arena_matches_records() {
+    let data = "h1,h2\na,b\nc,d\ne,f\n";
+    let expected = Reader::from_string(data)
+                          .records()
+                          .collect::<Result<Vec<_>>>()
+                          .unwrap();
+    for n in 1..5 {
+        let mut rdr = Reader::from_string(data);
+        let batches = arena_rows(&mut rdr, n);
+        assert_eq!(batches.len(), (3 + n - 1) / n);
+        let rows: Vec<Vec<String>> =
+            batches.into_iter().flat_map(|b| b.unwrap()).collect();
+        assert_eq!(rows, expected);
+    }
+}
+
+#[test]
+fn arena_no_headers() {
+    let mut rdr = Reader::from_string("a,b\nc,d").has_headers(false);
+    let rows = arena_rows(&mut rdr, 10);
+    assert_eq!(rows.len(), 1);
+    assert_svec_eq(rows.into_iter().next().unwrap().unwrap(),
+                   vec![vec!["a", "b"], vec!["c", "d"]]);
+}
+
+#[test]
+fn arena_invalid_utf8() {
+    let mut rdr = Reader::from_bytes(&b"a,b\n\xff,c\nd,e\n"[..])
+                         .has_headers(false);
+    let mut rows = arena_rows(&mut rdr, 10).into_iter();
+    assert_svec_eq(rows.next().unwrap().unwrap(), vec![vec!["a", "b"]]);
+    assert!(rows.next().unwrap().is_err());
+    assert_svec_eq(rows.next().unwrap().unwrap(), vec![vec!["d", "e"]]);
+    assert!(rows.next().is_none());
+}
+
+#[test]
+fn arena_char_split_across_fields() {
+    let mut rdr = Reader::from_bytes(&b"\xC3,\xA9\na,b\n"[..])
+                         .has_headers(false);
+    let mut rows = arena_rows(&mut rdr, 10).into_iter();
+    match rows.next().unwrap() {
+        Err(Error::Decode(_)) => {}
+        r => panic!("expected decode error but got {:?}", r),
+    }
+    assert_svec_eq(rows.next().unwrap().unwrap(), vec![vec!["a", "b"]]);
+    assert!(rows.next().is_none());
+}
+
+#[test]
+fn seek_to_start_two_passes() {
+    let data = "\nh1,h2\na,b\nc,d\n";
+    for &has_headers in &[true, false] {
+        let mut rdr = Reader::from_string(data).has_headers(has_headers);
+        let pass1 = rdr.records().collect::<Result<Vec<_>>>().unwrap();
+        rdr.seek_to_start().unwrap();
+        let pass2 = rdr.records().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(pass1.len(), if has_headers { 2 } else { 3 });
+        assert_eq!(pass1, pass2);
+    }
+}
+
+#[test]
+fn seek_to_start_before_reading() {
+    let mut rdr = Reader::from_string("h1,h2\na,b\n");
+    rdr.seek_to_start().unwrap();
+    let rows = rdr.records().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"]]);
+}
+
+#[test]
+fn invalid_utf8_resumes() {
+    let data = &b"a,b\nx\xff,y\nc,d\n"[..];
+    let mut d = Reader::from_bytes(data).has_headers(false);
+    let mut it = d.records();
+    assert_eq!(it.next().unwrap().unwrap(), vec!["a", "b"]);
+    assert!(it.next().unwrap().is_err());
+    assert_eq!(it.next().unwrap().unwrap(), vec!["c", "d"]);
+    assert!(it.next().is_none());
+}
+
+#[test]
+fn invalid_utf8_offset_after_record() {
+    let data = &b"a,b\nx\xff,y\nc,d\n"[..];
+    let mut d = Reader::from_bytes(data).has_headers(false);
+    d.records().nth(1).unwrap().unwrap_err();
+    assert_eq!(d.byte_offset(), 9);
+}
+
+parses_to!(preserve_quotes_doubled, r#""a""b",c"#,
+           vec![vec![r#""a""b""#, "c"]],
+           |rdr: Reader<_>| rdr.preserve_quotes(true));
+parses_to!(preserve_quotes_delim, "\"a,b\"\n\"c\nd\"",
+           vec![vec!["\"a,b\""], vec!["\"c\nd\""]],
+           |rdr: Reader<_>| rdr.preserve_quotes(true));
+parses_to!(preserve_quotes_escape, r#""a\"b""#, vec![vec![r#""a\"b""#]],
+           |rdr: Reader<_>| rdr.preserve_quotes(true).escape(Some(b'\\')));
+parses_to!(preserve_quotes_unquoted, "a,b", vec![vec!["a", "b"]],
+           |rdr: Reader<_>| rdr.preserve_quotes(true));
+
+parses_to!(max_field_lines_under, "\"a\r\nb\nc\",d",
+           vec![vec!["a\r\nb\nc", "d"]],
+           |rdr: Reader<_>| rdr.max_field_lines(Some(2)));
+fail_parses_to!(max_field_lines_over, "\"a\nb\nc\nd\",e", vec![],
+                |rdr: Reader<_>| rdr.max_field_lines(Some(2)));
+parses_to!(max_field_lines_unquoted, "a\nb\nc\nd",
+           vec![vec!["a"], vec!["b"], vec!["c"], vec!["d"]],
+           |rdr: Reader<_>| rdr.max_field_lines(Some(0)));
+
+#[test]
+fn max_field_lines_error() {
+    let mut d = Reader::from_string("a\n\"b\nc\nd\"\n")
+                       .has_headers(false)
+                       .max_field_lines(Some(1));
+    let err = d.records().nth(1).unwrap().unwrap_err();
+    match err {
+        Error::Parse(LocatableError {
+            record: 2,
+            err: ParseError::FieldTooManyLines { max: 1 }, ..
+        }) => {}
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+parses_to!(max_fields_under, "a,b,c\nd,e,f", vec![vec!["a", "b", "c"],
+                                                 vec!["d", "e", "f"]],
+           |rdr: Reader<_>| rdr.max_fields(Some(3)));
+
+#[test]
+fn max_fields_over() {
+    let mut d = Reader::from_string("a,b,c\n,,,\n").has_headers(false)
+                       .flexible(true)
+                       .max_fields(Some(3));
+    let mut rows = d.records();
+    assert_eq!(rows.next().unwrap().unwrap(), vec!["a", "b", "c"]);
+    match rows.next().unwrap().unwrap_err() {
+        Error::Parse(LocatableError {
+            record: 2,
+            field: 3,
+            err: ParseError::TooManyFields { max: 3 },
+        }) => {}
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[test]
+fn max_fields_adversarial() {
+    let data = format!("a\n{}\nb\n", ::std::iter::repeat(",")
+                                          .take(1_000_000)
+                                          .collect::<String>());
+    let mut d = Reader::from_string(data).flexible(true)
+                                          .max_fields(Some(10));
+    match d.records().next().unwrap().unwrap_err() {
+        Error::Parse(LocatableError {
+            err: ParseError::TooManyFields { max: 10 }, ..
+        }) => {}
+        err => panic!("unexpected error: {:?}", err),
+    }
+    // The error is reported long before the end of the line.
+    assert!(d.byte_offset() < 20);
+}
+
+#[test]
+fn max_fields_header() {
+    let mut d = Reader::from_string("a,b,c\nd,e").max_fields(Some(2));
+    assert!(d.headers().is_err());
+}
+
+#[test]
+fn parse_error_field_first_record() {
+    // Fields are numbered from 0 in the first record, just like in the
+    // records after it.
+    let cases = [("a,\"b\nc\"\n", 1), ("x,y\na,\"b\nc\"\n", 2)];
+    for &(data, record) in &cases {
+        let mut d = Reader::from_string(data)
+                           .has_headers(false)
+                           .max_field_lines(Some(0));
+        let err = d.records().nth(record - 1).unwrap().unwrap_err();
+        match err {
+            Error::Parse(LocatableError {
+                record: r,
+                field: 1,
+                err: ParseError::FieldTooManyLines { max: 0 },
+            }) if r == record as u64 => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
+
+#[test]
+fn records_as_set_keys() {
+    use std::collections::HashSet;
+
+    let data = "a,b\nc,d\na,b\n";
+    let mut d = Reader::from_string(data).has_headers(false);
+    let set = d.records().collect::<Result<HashSet<_>>>().unwrap();
+    assert_eq!(set.len(), 2);
+
+    let mut d = Reader::from_string(data).has_headers(false);
+    let set = d.byte_records().collect::<Result<HashSet<_>>>().unwrap();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&vec![bytes("c"), bytes("d")]));
+}
+
+#[test]
+fn peek_first_field_headers() {
+    let mut d = Reader::from_string("h1,h2\na,b\nc,d");
+    assert_eq!(d.peek_first_field().unwrap(), Some(&b"a"[..]));
+    assert_eq!(d.peek_first_field().unwrap(), Some(&b"a"[..]));
+    let rows = d.records().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["c", "d"]]);
+    assert_eq!(d.peek_first_field().unwrap(), None);
+}
+
+#[test]
+fn peek_first_field_no_headers() {
+    let mut d = Reader::from_string("a,b\nc,d").has_headers(false);
+    d.headers().unwrap();
+    assert_eq!(d.peek_first_field().unwrap(), Some(&b"a"[..]));
+    let rows = d.records().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["c", "d"]]);
+}
+
+#[test]
+fn no_headers_many_iterators() {
+    let mut d = Reader::from_string("a,b\nc,d").has_headers(false);
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["a", "b"]);
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["c", "d"]);
+    assert!(d.records().next().is_none());
+}
+
+#[test]
+fn dedup_records_adjacent() {
+    let data = "h\na\na\nb\nb\nb\na\n";
+    let mut d = Reader::from_string(data);
+    let rows = d.dedup_records().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["a"], vec!["b"], vec!["a"]]);
+}
+
+#[test]
+fn dedup_records_errors() {
+    let data = &b"a\n\xff\na\nb\n"[..];
+    let mut d = Reader::from_bytes(data).has_headers(false);
+    let rows: Vec<_> = d.dedup_records().collect();
+    assert_eq!(rows.len(), 3);
+    assert!(rows[1].is_err());
+    assert_eq!(rows[2].as_ref().unwrap(), &vec!["b"]);
+}
+
+#[test]
+fn dedup_consecutive_by_column() {
+    let data = "k,v\nx,1\nx,2\ny,2\ny,3\nx,4\n";
+    let mut d = Reader::from_string(data);
+    let rows = d.dedup_consecutive_by(0)
+                .collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["x", "1"], vec!["y", "2"], vec!["x", "4"]]);
+}
+
+#[test]
+fn dedup_consecutive_by_missing_column() {
+    let data = "a,1\na\na\na,2\na,3\n";
+    let mut d = Reader::from_string(data).has_headers(false).flexible(true);
+    let rows = d.dedup_consecutive_by(1)
+                .collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![
+        vec!["a", "1"], vec!["a"], vec!["a"], vec!["a", "2"], vec!["a", "3"],
+    ]);
+}
+
+#[test]
+fn dedup_consecutive_by_errors() {
+    let data = &b"a,1\n\xff,1\nb,1\nc,2\n"[..];
+    let mut d = Reader::from_bytes(data).has_headers(false);
+    let rows: Vec<_> = d.dedup_consecutive_by(1).collect();
+    assert_eq!(rows.len(), 3);
+    assert!(rows[1].is_err());
+    assert_eq!(rows[2].as_ref().unwrap(), &vec!["c", "2"]);
+}
+
+#[test]
+fn seek_inside_crlf() {
+    let data = "a,b\r\nc,d\r\ne,f\r\n";
+    let mut d = Reader::from_string(data).has_headers(false);
+    // Offset 4 is the `\n` of the first record's terminator.
+    d.seek(4).unwrap();
+    let rows = d.records().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["c", "d"], vec!["e", "f"]]);
+}
+
+#[test]
+fn merge_sorted_readers() {
+    let rdrs = vec![
+        Reader::from_string("k,v\n1,a\n3,a\n3,b\n"),
+        Reader::from_string("k,v\n"),
+        Reader::from_string("k,v\n2,c\n3,c\n4,c\n"),
+    ];
+    let merged = ::MergeReader::new(rdrs, |r: &[String]| r[0].clone());
+    let rows = merged.collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![
+        vec!["1", "a"], vec!["2", "c"], vec!["3", "a"],
+        vec!["3", "b"], vec!["3", "c"], vec!["4", "c"],
+    ]);
+}
+
+#[test]
+fn merge_drops_failed_reader() {
+    let rdrs = vec![
+        Reader::from_string("1\n2,x\n3\n").has_headers(false),
+        Reader::from_string("1\n4\n").has_headers(false),
+    ];
+    let merged = ::MergeReader::new(rdrs, |r: &[String]| r[0].clone());
+    let rows: Vec<_> = merged.collect();
+    assert_eq!(rows.len(), 4);
+    assert!(rows[1].is_err());
+    assert_eq!(rows[2].as_ref().unwrap(), &vec!["1"]);
+    assert_eq!(rows[3].as_ref().unwrap(), &vec!["4"]);
+}
+
+#[test]
+fn headers_empty_input() {
+    for &has_headers in &[true, false] {
+        let mut d = Reader::from_bytes(&b""[..]).has_headers(has_headers);
+        assert_eq!(d.headers().unwrap().len(), 0);
+        assert!(d.records().next().is_none());
+        assert_eq!(d.byte_headers().unwrap().len(), 0);
+        assert!(d.done());
+    }
+}
+
+struct CountingWriter {
+    writes: usize,
+    bytes: Vec<u8>,
+}
+
+impl io::Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writes += 1;
+        self.bytes.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+#[test]
+fn wtr_buffer_capacity() {
+    let mut counter = CountingWriter { writes: 0, bytes: vec![] };
+    {
+        let mut wtr = Writer::from_writer(&mut counter)
+                             .buffer_capacity(1 << 16);
+        for _ in 0..1000 {
+            wtr.write(vec!["abc"; 10].into_iter()).unwrap();
+        }
+        wtr.flush().unwrap();
+    }
+    assert_eq!(counter.bytes.len(), 1000 * 40);
+    assert!(counter.writes <= 1, "too many writes: {}", counter.writes);
+}
+
+#[test]
+fn into_records_with_headers() {
+    let d = Reader::from_string("h1,h2\na,b\nc,d");
+    let (headers, it) = d.into_records_with_headers().unwrap();
+    assert_eq!(headers, vec!["h1", "h2"]);
+    let rows = it.collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["c", "d"]]);
+
+    let d = Reader::from_string("h1,h2\na,b").has_headers(false);
+    let (headers, it) = d.into_records_with_headers().unwrap();
+    assert_eq!(headers, vec!["h1", "h2"]);
+    assert_eq!(it.count(), 2);
+}
+
+#[test]
+fn into_records_with_headers_only() {
+    let d = Reader::from_string("h1,h2\n");
+    let (headers, mut it) = d.into_records_with_headers().unwrap();
+    assert_eq!(headers, vec!["h1", "h2"]);
+    assert!(it.next().is_none());
+}
+
+parses_to!(trim_chars_stars, "**a*b**,c*\n*,**",
+           vec![vec!["a*b", "c"], vec!["", ""]],
+           |rdr: Reader<_>| rdr.trim_chars(b"*"));
+parses_to!(trim_chars_spaces, "  a  , b\n\" c \",d ",
+           vec![vec!["a", "b"], vec!["c", "d"]],
+           |rdr: Reader<_>| rdr.trim_chars(b" "));
+parses_to!(trim_chars_mixed, "* a *,b", vec![vec!["a", "b"]],
+           |rdr: Reader<_>| rdr.trim_chars(b" *"));
+parses_to!(trim_chars_none, " a ", vec![vec![" a "]],
+           |rdr: Reader<_>| rdr.trim_chars(b""));
+
+#[test]
+fn trim_chars_headers() {
+    let mut d = Reader::from_string(" h1 , h2 \na,b").trim_chars(b" ");
+    assert_eq!(d.headers().unwrap(), vec!["h1", "h2"]);
+}
+
+#[test]
+fn byte_offset_per_field() {
+    let mut d = Reader::from_string("ab,cde,f\nx\n");
+    let mut offsets = vec![];
+    while let Some(r) = d.next_bytes().into_iter_result() {
+        r.unwrap();
+        offsets.push(d.byte_offset());
+    }
+    assert_eq!(offsets, vec![3, 7, 9]);
+}
+
+#[test]
+fn read_record_into() {
+    use ReadInto;
+
+    let mut d = Reader::from_string("a,bc,d\nefg,h,i\n");
+    let (mut fields, mut ends) = ([0; 16], [0; 2]);
+
+    let r = d.read_record_into(&mut fields, &mut ends).unwrap();
+    assert_eq!(r, ReadInto::OutputEndsFull { fields: 2, bytes: 3 });
+    assert_eq!(&fields[..3], b"abc");
+    assert_eq!(ends, [1, 3]);
+
+    let r = d.read_record_into(&mut fields, &mut ends).unwrap();
+    assert_eq!(r, ReadInto::Record { fields: 1, bytes: 1 });
+    assert_eq!(&fields[..1], b"d");
+
+    let r = d.read_record_into(&mut fields, &mut ends[..1]).unwrap();
+    assert_eq!(r, ReadInto::OutputEndsFull { fields: 1, bytes: 3 });
+    assert_eq!(&fields[..ends[0]], b"efg");
+
+    let r = d.read_record_into(&mut fields[3..], &mut ends[1..]).unwrap();
+    assert_eq!(r, ReadInto::OutputEndsFull { fields: 1, bytes: 1 });
+    assert_eq!(&fields[3..3 + ends[1]], b"h");
+
+    let r = d.read_record_into(&mut fields, &mut ends).unwrap();
+    assert_eq!(r, ReadInto::Record { fields: 1, bytes: 1 });
+    assert_eq!(&fields[..1], b"i");
+
+    let r = d.read_record_into(&mut fields, &mut ends).unwrap();
+    assert_eq!(r, ReadInto::End);
+}
+
+#[test]
+fn read_record_into_output_full() {
+    use ReadInto;
+
+    let mut d = Reader::from_string("abcde\n");
+    let mut fields = [0; 2];
+    let mut ends = [0; 1];
+    let mut got = vec![];
+    loop {
+        match d.read_record_into(&mut fields, &mut ends).unwrap() {
+            ReadInto::OutputFull { bytes, .. } => {
+                got.extend_from_slice(&fields[..bytes]);
+            }
+            ReadInto::Record { bytes, .. } => {
+                got.extend_from_slice(&fields[..bytes]);
+                break;
+            }
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+    assert_eq!(got, b"abcde");
+    assert_eq!(ends[0], 1);
+}
+
+/// A reader that reports `UnexpectedEof` once its data is exhausted.
+struct EofErrReader {
+    data: io::Cursor<Vec<u8>>,
+}
+
+impl io::Read for EofErrReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match try!(self.data.read(buf)) {
+            0 => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "eof")),
+            n => Ok(n),
+        }
+    }
+}
+
+fn eof_err_reader(s: &str) -> Reader<EofErrReader> {
+    let data = io::Cursor::new(s.as_bytes().to_vec());
+    Reader::from_reader(EofErrReader { data: data }).has_headers(false)
+}
+
+#[test]
+fn unexpected_eof_at_boundary() {
+    let mut d = eof_err_reader("a,b\nc,d\n");
+    let rows = d.records().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["c", "d"]]);
+
+    let mut d = eof_err_reader("a,b\r\n");
+    let rows = d.records().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"]]);
+
+    let mut d = eof_err_reader("");
+    assert_eq!(d.records().count(), 0);
+}
+
+#[test]
+fn unexpected_eof_mid_record() {
+    let mut d = eof_err_reader("a,b\nc,d");
+    let mut it = d.records();
+    assert_eq!(it.next().unwrap().unwrap(), vec!["a", "b"]);
+    match it.next() {
+        Some(Err(Error::Io(ref err)))
+            if err.kind() == io::ErrorKind::UnexpectedEof => {}
+        r => panic!("expected an unexpected EOF error, got {:?}", r),
+    }
+}
+
+#[test]
+fn collect_until_error() {
+    let mut d = Reader::from_bytes(&b"h1,h2\na,b\nc,\xff\ne,f"[..]);
+    let (rows, err) = d.records().collect_until_error();
+    assert_svec_eq(rows, vec![vec!["a", "b"]]);
+    let (offset, err) = err.unwrap();
+    assert_eq!(offset, 10);
+    match err {
+        Error::Decode(_) => {}
+        err => panic!("expected a UTF-8 error, got {:?}", err),
+    }
+
+    let mut d = Reader::from_string("h1,h2\na,b\nc,d");
+    let (rows, err) = d.records().collect_until_error();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["c", "d"]]);
+    assert!(err.is_none());
+}
+
+#[test]
+fn peek_headers_order_independent() {
+    let data = "a,b\n1,2\n3,4";
+
+    let mut d = Reader::from_string(data).has_headers(false);
+    let headers1 = d.peek_headers().unwrap().to_vec();
+    let rows1 = d.records().collect::<Result<Vec<_>>>().unwrap();
+
+    let mut d = Reader::from_string(data).has_headers(false);
+    let rows2 = d.records().collect::<Result<Vec<_>>>().unwrap();
+    let headers2 = d.peek_headers().unwrap().to_vec();
+
+    let mut d = Reader::from_string(data).has_headers(false);
+    let mut rows3 = vec![];
+    {
+        let mut it = d.byte_records();
+        rows3.push(it.next().unwrap().unwrap());
+    }
+    let headers3 = d.peek_headers().unwrap().to_vec();
+    rows3.extend(d.byte_records().map(|r| r.unwrap()));
+
+    assert_eq!(headers1, vec![b"a".to_vec(), b"b".to_vec()]);
+    assert_eq!(headers1, headers2);
+    assert_eq!(headers1, headers3);
+    assert_eq!(rows1, rows2);
+    assert_svec_eq(rows1,
+                   vec![vec!["a", "b"], vec!["1", "2"], vec!["3", "4"]]);
+    assert_eq!(rows3.len(), 3);
+}
+
+parses_to!(relaxed_quotes_mid_field, r#"foo"bar"baz,x"#,
+           vec![vec![r#"foo"bar"baz"#, "x"]],
+           |rdr: Reader<_>| rdr.relaxed_quotes(true));
+parses_to!(relaxed_quotes_trailing, r#""quoted"trailing,x"#,
+           vec![vec![r#"quoted"trailing"#, "x"]],
+           |rdr: Reader<_>| rdr.relaxed_quotes(true));
+parses_to!(relaxed_quotes_normal, "\"a,b\",\"c\"\"d\"",
+           vec![vec!["a,b", "c\"d"]],
+           |rdr: Reader<_>| rdr.relaxed_quotes(true));
+parses_to!(strict_quotes_mid_field, r#"foo"bar"baz,x"#,
+           vec![vec![r#"foo"bar"baz"#, "x"]]);
+parses_to!(strict_quotes_trailing, r#""quoted"trailing,x"#,
+           vec![vec!["quotedtrailing", "x"]]);
+
+#[test]
+fn lookahead() {
+    let mut d = Reader::from_string("h\na\nb\nc\n");
+    let mut look = d.with_lookahead(2);
+    assert!(look.current().is_none());
+
+    let mut seen = vec![];
+    while look.advance().unwrap() {
+        let cur = look.current().unwrap()[0].clone();
+        let next = look.peek(0).map(|r| r[0].clone());
+        let after = look.peek(1).map(|r| r[0].clone());
+        assert!(look.peek(2).is_none());
+        seen.push((cur, next, after));
+    }
+    let s = |s: &str| s.to_string();
+    assert_eq!(seen, vec![
+        (s("a"), Some(s("b")), Some(s("c"))),
+        (s("b"), Some(s("c")), None),
+        (s("c"), None, None),
+    ]);
+    assert!(look.current().is_none());
+    assert!(!look.advance().unwrap());
+}
+
+#[test]
+fn lookahead_error() {
+    let mut d = Reader::from_string("a\nb\nc,d\ne").has_headers(false);
+    let mut look = d.with_lookahead(3);
+    assert!(look.advance().unwrap());
+    assert!(look.peek(0).is_some());
+    assert!(look.peek(1).is_none());
+    assert!(look.advance().unwrap());
+    assert!(look.advance().is_err());
+}
+
+#[test]
+fn collect_records_stops_at_first_error() {
+    let mut d = Reader::from_bytes(&b"a\n\xff\nb\n\xfe\nc"[..])
+                       .has_headers(false);
+    match d.collect_records() {
+        Err(Error::Decode(ref msg)) => assert!(msg.contains("255")),
+        r => panic!("expected a decode error, got {:?}", r),
+    }
+    // Nothing after the first error was read.
+    let r = d.records().collect::<Result<Vec<_>>>();
+    match r {
+        Err(Error::Decode(ref msg)) => assert!(msg.contains("254")),
+        r => panic!("expected a decode error, got {:?}", r),
+    }
+    assert_svec_eq(d.collect_records().unwrap(), vec![vec!["c"]]);
+}
+
+parses_to!(escape_quoted_only_delim, r#"a\,b"#, vec![vec![r"a\", "b"]],
+           |rdr: Reader<_>| rdr.escape(Some(b'\\')).flexible(true));
+parses_to!(escape_quoted_only_quote, r#"a\"b"#, vec![vec![r#"a\"b"#]],
+           |rdr: Reader<_>| rdr.escape(Some(b'\\')));
+parses_to!(escape_quoted_only_quoted, r#""a\"b""#, vec![vec![r#"a"b"#]],
+           |rdr: Reader<_>| rdr.escape(Some(b'\\')));
+parses_to!(escape_everywhere_delim, r#"a\,b"#, vec![vec!["a,b"]],
+           |rdr: Reader<_>| rdr.escape(Some(b'\\'))
+                               .escape_mode(EscapeMode::Everywhere));
+parses_to!(escape_everywhere_quote, r#"\"a\"b"#, vec![vec![r#""a"b"#]],
+           |rdr: Reader<_>| rdr.escape(Some(b'\\'))
+                               .escape_mode(EscapeMode::Everywhere));
+parses_to!(escape_everywhere_quoted, r#""a\"b""#, vec![vec![r#"a"b"#]],
+           |rdr: Reader<_>| rdr.escape(Some(b'\\'))
+                               .escape_mode(EscapeMode::Everywhere));
+parses_to!(escape_everywhere_newline, "a\\\nb,c", vec![vec!["a\nb", "c"]],
+           |rdr: Reader<_>| rdr.escape(Some(b'\\'))
+                               .escape_mode(EscapeMode::Everywhere));
+
+writes_as!(wtr_escape_escape, vec![vec!["a\\\"b,c"]], "\"a\\\\\\\"b,c\"\n",
+           |wtr: Writer<_>| wtr.double_quote(false));
+
+#[test]
+fn escape_round_trip() {
+    let rows = vec![
+        vec![r#"a"b"#, r"c\d", r#"e\"f,g"#],
+        vec!["plain", r"\", "\""],
+    ];
+    let mut wtr = Writer::from_memory().double_quote(false).escape(b'\\');
+    for row in &rows {
+        wtr.write(row.iter()).unwrap();
+    }
+    let data = wtr.into_bytes();
+    let mut rdr = Reader::from_bytes(data)
+                         .has_headers(false)
+                         .escape(Some(b'\\'))
+                         .double_quote(false);
+    let got = rdr.collect_records().unwrap();
+    assert_svec_eq(got, rows);
+}
+
+parses_to!(escape_lenient_path, r#""C:\path",x"#, vec![vec![r"C:\path", "x"]],
+           |rdr: Reader<_>| rdr.escape(Some(b'\\')));
+parses_to!(escape_lenient_escape, r#""a\\b""#, vec![vec![r"a\b"]],
+           |rdr: Reader<_>| rdr.escape(Some(b'\\')));
+parses_to!(escape_lenient_preserve, r#""C:\path""#, vec![vec![r#""C:\path""#]],
+           |rdr: Reader<_>| rdr.escape(Some(b'\\')).preserve_quotes(true));
+parses_to!(escape_strict_valid, r#""a\"b\\c""#, vec![vec![r#"a"b\c"#]],
+           |rdr: Reader<_>| rdr.escape(Some(b'\\')).strict_escape(true));
+
+#[test]
+fn escape_strict_path() {
+    let mut d = Reader::from_string("a,b\n\"C:\\path\",x")
+                       .escape(Some(b'\\'))
+                       .strict_escape(true);
+    let err = d.records().next().unwrap().unwrap_err();
+    match err {
+        Error::Parse(LocatableError {
+            record: 2,
+            err: ParseError::InvalidEscape { pos: 7 }, ..
+        }) => {}
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+parses_to!(whitespace_line_kept, "a\n   \n", vec![vec!["a"], vec!["   "]]);
+parses_to!(whitespace_line_ignored, "a\n \t \r\nb\n  ",
+           vec![vec!["a"], vec!["b"]],
+           |rdr: Reader<_>| rdr.ignore_whitespace_only_lines(true));
+parses_to!(whitespace_line_first, "  \na\n", vec![vec!["a"]],
+           |rdr: Reader<_>| rdr.ignore_whitespace_only_lines(true));
+parses_to!(whitespace_line_value, "a\n b\n", vec![vec!["a"], vec![" b"]],
+           |rdr: Reader<_>| rdr.ignore_whitespace_only_lines(true));
+parses_to!(whitespace_line_quoted, "a\n\"  \"\n", vec![vec!["a"], vec!["  "]],
+           |rdr: Reader<_>| rdr.ignore_whitespace_only_lines(true));
+parses_to!(whitespace_line_fields, "a,b\n , \n",
+           vec![vec!["a", "b"], vec![" ", " "]],
+           |rdr: Reader<_>| rdr.ignore_whitespace_only_lines(true));
+
+#[test]
+fn whitespace_line_length_check() {
+    let mut d = Reader::from_string("a,b\n1,2\n  \n3,4\n")
+                       .ignore_whitespace_only_lines(true);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["1", "2"], vec!["3", "4"]]);
+}
+
+parses_to!(trailing_quote_escaped, r#""ab""cd",x"#,
+           vec![vec![r#"ab"cd"#, "x"]],
+           |rdr: Reader<_>| rdr.error_trailing_quote_content(true));
+parses_to!(trailing_quote_closed, "\"ab\",\"cd\"\r\n\"ef\"",
+           vec![vec!["ab", "cd"], vec!["ef"]],
+           |rdr: Reader<_>| rdr.error_trailing_quote_content(true)
+                               .flexible(true));
+parses_to!(trailing_quote_lenient, r#""ab"x,y"#, vec![vec!["abx", "y"]]);
+
+#[test]
+fn trailing_quote_content() {
+    let mut d = Reader::from_string("a,b\nc,\"ab\"x")
+                       .error_trailing_quote_content(true);
+    let err = d.records().next().unwrap().unwrap_err();
+    match err {
+        Error::Parse(LocatableError {
+            record: 2,
+            err: ParseError::TrailingQuoteContent { pos: 10 }, ..
+        }) => {}
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[test]
+fn trailing_quote_content_no_double_quote() {
+    let mut d = Reader::from_string(r#""ab""cd""#)
+                       .has_headers(false)
+                       .double_quote(false)
+                       .error_trailing_quote_content(true);
+    assert!(d.records().next().unwrap().is_err());
+}
+
+#[test]
+fn header_line() {
+    let data = "title\r\n\r\nsum,1,2,3\r\nh1,h2\r\na,b\r\n\r\nc,d";
+    let mut d = Reader::from_string(data).header_line(3);
+    assert_eq!(d.headers().unwrap(), vec!["h1", "h2"]);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["c", "d"]]);
+
+    d.seek_to_start().unwrap();
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["c", "d"]]);
+}
+
+#[test]
+fn header_line_no_headers() {
+    let d = Reader::from_string("junk\nh1,h2\na,b");
+    let mut d = d.header_line(1).has_headers(false);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["h1", "h2"], vec!["a", "b"]]);
+}
+
+#[test]
+fn skip_records_while() {
+    let mut d = Reader::from_string("x\nx\na\nx\n").has_headers(false);
+    assert_eq!(d.skip_records_while(|r| r[0] == "x").unwrap(), 2);
+    assert_eq!(d.skip_records_while(|r| r[0] == "x").unwrap(), 0);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["a"], vec!["x"]]);
+
+    let mut d = Reader::from_string("x\nx\n").has_headers(false);
+    assert_eq!(d.skip_records_while(|r| r[0] == "x").unwrap(), 2);
+    assert!(d.collect_records().unwrap().is_empty());
+}
+
+#[test]
+fn take_records_while() {
+    let mut d = Reader::from_string("h\na\nb\n\"\"\nc\n");
+    let rows = d.take_records_while(|r| r[0] != "")
+                .collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["a"], vec!["b"]]);
+    // The sentinel record is not consumed.
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec![""], vec!["c"]]);
+}
+
+#[test]
+fn peek_field_count() {
+    let mut d = Reader::from_string("h1,h2\n\"a,b\",c\nd\n").flexible(true);
+    assert_eq!(d.peek_field_count().unwrap(), Some(2));
+    assert_eq!(d.peek_field_count().unwrap(), Some(2));
+    assert_eq!(d.byte_offset(), 14);
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["a,b", "c"]);
+    assert_eq!(d.peek_field_count().unwrap(), Some(1));
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["d"]);
+    assert_eq!(d.peek_field_count().unwrap(), None);
+}
+
+#[test]
+fn total_bytes_read() {
+    let mut d = Reader::from_string("a,b\nc,d\ne,f\n");
+    assert_eq!(d.total_bytes_read(), 0);
+    d.collect_records().unwrap();
+    assert_eq!(d.total_bytes_read(), 12);
+
+    d.seek(4).unwrap();
+    assert_eq!(d.total_bytes_read(), 12);
+    d.collect_records().unwrap();
+    assert_eq!(d.total_bytes_read(), 20);
+
+    d.seek_to_start().unwrap();
+    d.collect_records().unwrap();
+    assert_eq!(d.total_bytes_read(), 32);
+    assert_eq!(d.byte_offset(), 12);
+}
+
+fn on_error_rows(data: &[u8], policy: Option<OnError>)
+                -> Vec<Result<Vec<String>>> {
+    let mut d = Reader::from_bytes(data);
+    if let Some(policy) = policy {
+        d = d.on_error(policy);
+    }
+    d.records().collect()
+}
+
+#[test]
+fn on_error_unequal_lengths() {
+    let data = b"h1,h2\na,b\nc\nd,e\n";
+    for &policy in &[None, Some(OnError::Abort)] {
+        let rows = on_error_rows(data, policy);
+        assert_eq!(rows.len(), 2);
+        assert!(rows[1].is_err());
+    }
+    for &policy in &[OnError::Skip, OnError::Lossy] {
+        let rows = on_error_rows(data, Some(policy));
+        let rows = rows.into_iter().collect::<Result<Vec<_>>>().unwrap();
+        assert_svec_eq(rows, vec![vec!["a", "b"], vec!["d", "e"]]);
+    }
+}
+
+#[test]
+fn on_error_invalid_utf8() {
+    let data = b"h1,h2\na,b\nc,\xff\nd,e\n";
+
+    let rows = on_error_rows(data, None);
+    assert_eq!(rows.len(), 3);
+    assert!(rows[1].is_err());
+
+    let rows = on_error_rows(data, Some(OnError::Abort));
+    assert_eq!(rows.len(), 2);
+    assert!(rows[1].is_err());
+
+    let rows = on_error_rows(data, Some(OnError::Skip));
+    let rows = rows.into_iter().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["d", "e"]]);
+
+    let rows = on_error_rows(data, Some(OnError::Lossy));
+    let rows = rows.into_iter().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![
+        vec!["a", "b"], vec!["c", "\u{FFFD}"], vec!["d", "e"],
+    ]);
+}
+
+#[test]
+fn on_error_skip_byte_records() {
+    let mut d = Reader::from_string("a,b\nc\nd,e\nf\n")
+                       .has_headers(false)
+                       .on_error(OnError::Skip);
+    let rows = d.byte_records().collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(rows.len(), 2);
+}
+
+#[test]
+fn on_error_into_records_with_headers() {
+    let data = &b"h1,h2\na,b\nc,\xff\nd\ne,f\n"[..];
+
+    let d = Reader::from_bytes(data).on_error(OnError::Skip);
+    let (_, it) = d.into_records_with_headers().unwrap();
+    let rows = it.collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["e", "f"]]);
+
+    let d = Reader::from_bytes(data).on_error(OnError::Abort);
+    let (_, it) = d.into_records_with_headers().unwrap();
+    let rows = it.collect::<Vec<_>>();
+    assert_eq!(rows.len(), 2);
+    assert!(rows[1].is_err());
+}
+
+#[test]
+fn on_error_take_records_while() {
+    let data = &b"h1,h2\na,b\nc,\xff\nd\ne,f\n\"\",g\n"[..];
+
+    let mut d = Reader::from_bytes(data).on_error(OnError::Skip);
+    let rows = d.take_records_while(|r| r[0] != "")
+                .collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["e", "f"]]);
+    assert_eq!(d.collect_records().unwrap(), vec![vec!["", "g"]]);
+
+    let mut d = Reader::from_bytes(data).on_error(OnError::Abort);
+    let rows = d.take_records_while(|r| r[0] != "").collect::<Vec<_>>();
+    assert_eq!(rows.len(), 2);
+    assert!(rows[1].is_err());
+}
+
+#[test]
+fn reader_getters() {
+    let d = Reader::from_string("");
+    assert!(!d.is_flexible());
+    assert!(d.buffer_capacity() > 0);
+    assert!(Reader::from_string("").flexible(true).is_flexible());
+}
+
+#[test]
+fn project_reorder() {
+    let mut d = Reader::from_string("a,b,c\n1,2,3\n4,5,6")
+                       .project(vec![2, 0, 2]);
+    assert_eq!(d.headers().unwrap(), vec!["c", "a", "c"]);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["3", "1", "3"], vec!["6", "4", "6"]]);
+
+    let mut d = Reader::from_string("a,b\n1,2").has_headers(false)
+                       .project(vec![1]);
+    let rows = d.byte_records().collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(rows, vec![vec![b"b".to_vec()], vec![b"2".to_vec()]]);
+}
+
+#[test]
+fn project_out_of_range() {
+    let mut d = Reader::from_string("a,b\n1,2\n3").flexible(true)
+                       .project(vec![1, 5]);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["2", ""], vec!["", ""]]);
+
+    let mut d = Reader::from_string("a,b\n1,2\n3").flexible(true)
+                       .project(vec![1]).project_strict(true);
+    let mut it = d.records();
+    assert_eq!(it.next().unwrap().unwrap(), vec!["2"]);
+    match it.next() {
+        Some(Err(Error::Parse(LocatableError {
+            record: 3,
+            err: ParseError::MissingColumn { column: 1 },
+            ..
+        }))) => {}
+        r => panic!("expected a missing column error, got {:?}", r),
+    }
+}
+
+#[test]
+fn read_columns() {
+    let mut d = Reader::from_string("a,b,c\n1,2,3\n4,5,6\n");
+    let cols = d.read_columns(3).unwrap();
+    assert_svec_eq(cols, vec![vec!["1", "4"], vec!["2", "5"], vec!["3", "6"]]);
+
+    let mut d = Reader::from_string("1,2\n3,4").has_headers(false);
+    let cols = d.read_columns(2).unwrap();
+    assert_svec_eq(cols, vec![vec!["1", "3"], vec!["2", "4"]]);
+}
+
+#[test]
+fn read_columns_not_projected() {
+    let mut d = Reader::from_string("1,2,3\n4,5,6").has_headers(false)
+                                                  .project(vec![2]);
+    let cols = d.read_columns(3).unwrap();
+    assert_svec_eq(cols, vec![vec!["1", "4"], vec!["2", "5"], vec!["3", "6"]]);
+
+    let mut d = Reader::from_string("a,b,c\n1,2,3\n4,5,6").project(vec![2]);
+    assert_eq!(d.peek_first_field().unwrap(), Some(&b"3"[..]));
+    let cols = d.read_columns(3).unwrap();
+    assert_svec_eq(cols, vec![vec!["1", "4"], vec!["2", "5"], vec!["3", "6"]]);
+}
+
+#[test]
+fn read_columns_ragged() {
+    let data = "a,b\n1,2\n3\n4,5,6\n";
+    let mut d = Reader::from_string(data).flexible(true);
+    let cols = d.read_columns(2).unwrap();
+    assert_svec_eq(cols, vec![vec!["1", "3", "4"], vec!["2", "", "5"]]);
+
+    let mut d = Reader::from_string("a,b\n1,2\n");
+    match d.read_columns(3) {
+        Err(Error::Parse(LocatableError {
+            record: 2,
+            err: ParseError::UnequalLengths {
+                expected: 3, got: 2,
+            },
+            ..
+        })) => {}
+        r => panic!("expected an unequal lengths error, got {:?}", r),
+    }
+}
+
+#[test]
+fn read_record_array() {
+    let mut d = Reader::from_string("a,b,c\n1,2,3\n4,5\n6,7,8,9\n")
+                       .flexible(true);
+    let row: [String; 3] = d.read_record_array().unwrap().unwrap();
+    assert_eq!(row, ["1", "2", "3"]);
+    for &got in &[2, 4] {
+        match d.read_record_array::<[String; 3]>() {
+            Err(Error::Parse(LocatableError {
+                err: ParseError::UnequalLengths { expected: 3, got: g, .. },
+                ..
+            })) => assert_eq!(g, got),
+            r => panic!("expected an unequal lengths error, got {:?}", r),
+        }
+    }
+    assert!(d.read_record_array::<[String; 3]>().unwrap().is_none());
+}
+
+#[test]
+fn decode_fixed_array() {
+    let data = "a,b,c\n1,2,3\n4,5\n6,7,8,9\n";
+    let mut d = Reader::from_string(data).flexible(true);
+    let rows: Vec<Result<[String; 3]>> = d.decode().collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0].as_ref().unwrap(), &["1", "2", "3"]);
+    match rows[1] {
+        Err(Error::Decode(_)) => {}
+        ref r => panic!("expected a decode error, got {:?}", r),
+    }
+    match rows[2] {
+        Err(Error::Decode(_)) => {}
+        ref r => panic!("expected a decode error, got {:?}", r),
+    }
+}
+
+#[test]
+fn header_excluded_from_length_check() {
+    let data = "a,b,c,d\n1,2,3\n4,5,6\n";
+    let mut d = Reader::from_string(data)
+                       .header_excluded_from_length_check(true);
+    assert_eq!(d.headers().unwrap(), vec!["a", "b", "c", "d"]);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["1", "2", "3"], vec!["4", "5", "6"]]);
+
+    // Data records must still agree with each other.
+    let mut d = Reader::from_string("a,b,c,d\n1,2,3\n4,5\n")
+                       .header_excluded_from_length_check(true);
+    match d.collect_records() {
+        Err(Error::Parse(LocatableError {
+            err: ParseError::UnequalLengths {
+                expected: 3, got: 2,
+            }, ..
+        })) => {}
+        r => panic!("expected an unequal lengths error, got {:?}", r),
+    }
+
+    // Without the option, the header sets the length.
+    let mut d = Reader::from_string(data);
+    assert!(d.collect_records().is_err());
+}
+
+/// A reader that returns at most one byte per read.
+struct OneByteReader<R>(R);
+
+impl<R: io::Read> io::Read for OneByteReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = ::std::cmp::min(1, buf.len());
+        self.0.read(&mut buf[..n])
+    }
+}
+
+fn utf16_records<R: io::Read>(rdr: R) -> Vec<Vec<String>> {
+    Reader::from_reader_utf16(rdr).has_headers(false)
+                                  .collect_records().unwrap()
+}
+
+fn utf16(s: &str, big_endian: bool) -> Vec<u8> {
+    let mut bytes = if big_endian {
+        vec![0xFE, 0xFF]
+    } else {
+        vec![0xFF, 0xFE]
+    };
+    for u in s.encode_utf16() {
+        let (hi, lo) = ((u >> 8) as u8, u as u8);
+        if big_endian {
+            bytes.push(hi);
+            bytes.push(lo);
+        } else {
+            bytes.push(lo);
+            bytes.push(hi);
+        }
+    }
+    bytes
+}
+
+#[test]
+fn utf16_le_and_be() {
+    let data = "a,\u{e9}\n\u{1F600},\"x,y\"\n";
+    let expected = vec![vec!["a", "\u{e9}"], vec!["\u{1F600}", "x,y"]];
+    for &be in &[false, true] {
+        let bytes = utf16(data, be);
+        assert_svec_eq(utf16_records(&bytes[..]), expected.clone());
+        let rdr = OneByteReader(io::Cursor::new(bytes));
+        assert_svec_eq(utf16_records(rdr), expected.clone());
+    }
+}
+
+#[test]
+fn utf16_passthrough() {
+    let data = b"a,b\n\xFF\n";
+    let rdr = Reader::from_reader_utf16(OneByteReader(&data[..]));
+    let rows = rdr.has_headers(false).flexible(true)
+                  .byte_records().collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(rows, vec![vec![b"a".to_vec(), b"b".to_vec()],
+                          vec![b"\xFF".to_vec()]]);
+
+    assert!(utf16_records(&b""[..]).is_empty());
+}
+
+#[test]
+fn utf16_invalid() {
+    // An unpaired surrogate followed by an odd trailing byte.
+    let data = b"\xFF\xFEa\x00\x00\xD8,\x00b\x00c";
+    assert_svec_eq(utf16_records(&data[..]),
+                   vec![vec!["a\u{FFFD}", "b\u{FFFD}"]]);
+}
+
+#[test]
+fn wtr_raw_round_trip() {
+    let data = "h1,\"h2\"\n\"a\"\"b\",c\nd,\"e,f\"\n";
+    let mut rdr = Reader::from_string(data)
+                         .has_headers(false)
+                         .preserve_quotes(true);
+    let mut wtr = Writer::from_memory();
+    for (i, row) in rdr.byte_records().enumerate() {
+        let row = row.unwrap();
+        if i == 1 {
+            // Modified records are written normally.
+            wtr.write(vec!["x", "y,z"].into_iter()).unwrap();
+            continue;
+        }
+        let mut raw = row.join(&b',');
+        raw.push(b'\n');
+        wtr.write_raw(&raw).unwrap();
+    }
+    assert_eq!(wtr.as_string(), "h1,\"h2\"\nx,\"y,z\"\nd,\"e,f\"\n");
+}
+
+#[test]
+fn wtr_raw_capture_round_trip() {
+    let data = "h1,\"h2\"\r\n\"a\"\"b\", c \r\n\"x\ny\",\"e,f\"\nlast,1";
+    for &size in &[1, 3, data.len()] {
+        let parts: Vec<io::Result<Vec<u8>>> = data.as_bytes()
+            .chunks(size)
+            .map(|c| Ok(c.to_vec()))
+            .collect();
+        let mut rdr = Reader::from_chunks(parts.into_iter())
+                             .has_headers(false)
+                             .capture_raw(true);
+        let mut wtr = Writer::from_memory();
+        while let Some(row) = rdr.byte_records().next() {
+            row.unwrap();
+            wtr.write_raw(rdr.last_raw_bytes().unwrap()).unwrap();
+        }
+        assert_eq!(wtr.as_string(), data);
+    }
+}
+
+#[test]
+fn on_header() {
+    use std::sync::{Arc, Mutex};
+
+    let seen = Arc::new(Mutex::new(vec![]));
+    let seen2 = seen.clone();
+    let mut d = Reader::from_bytes(&b"a,b\xff\n1,2\n3,4"[..])
+                       .on_header(move |i, name| {
+        seen2.lock().unwrap().push((i, name.to_string()));
+    });
+    d.headers().unwrap_err();
+    d.byte_headers().unwrap();
+    assert_eq!(d.byte_records().count(), 2);
+    d.seek_to_start().unwrap();
+    assert_eq!(d.byte_records().count(), 2);
+    assert_eq!(*seen.lock().unwrap(),
+               vec![(0, "a".to_string()), (1, "b\u{FFFD}".to_string())]);
+
+    let seen = Arc::new(Mutex::new(0));
+    let seen2 = seen.clone();
+    let mut d = Reader::from_string("a,b\n1,2").has_headers(false)
+                       .on_header(move |_, _| *seen2.lock().unwrap() += 1);
+    d.collect_records().unwrap();
+    assert_eq!(*seen.lock().unwrap(), 0);
+}
+
+#[test]
+fn clear_headers_two_tables() {
+    let data = "a,b\n1,2\n3,4\n\nc,d,e\n5,6,7\n";
+    let mut d = Reader::from_string(data);
+    assert_eq!(d.headers().unwrap(), vec!["a", "b"]);
+    let mut rows = vec![];
+    {
+        let mut it = d.records();
+        rows.push(it.next().unwrap().unwrap());
+        rows.push(it.next().unwrap().unwrap());
+    }
+    assert_svec_eq(rows, vec![vec!["1", "2"], vec!["3", "4"]]);
+
+    d.clear_headers();
+    assert_eq!(d.headers().unwrap(), vec!["c", "d", "e"]);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["5", "6", "7"]]);
+}
+
+#[test]
+fn clear_headers_no_headers() {
+    let mut d = Reader::from_string("a\nb,c\nd,e").has_headers(false);
+    assert_eq!(d.peek_field_count().unwrap(), Some(1));
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["a"]);
+    d.clear_headers();
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["b", "c"], vec!["d", "e"]]);
+}
+
+#[test]
+fn clear_headers_projected_read_ahead() {
+    let data = "a,b,c\n1,2,3\nx,y,z\n4,5,6\n";
+    let mut d = Reader::from_string(data).project(vec![2, 0]);
+    let rows = d.take_records_while(|r| r[0] != "z")
+                .collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["3", "1"]]);
+    d.clear_headers();
+    assert_eq!(d.headers().unwrap(), vec!["z", "x"]);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["6", "4"]]);
+
+    let mut d = Reader::from_string(data).project(vec![2, 0]);
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["3", "1"]);
+    assert_eq!(d.peek_first_field().unwrap(), Some(&b"z"[..]));
+    d.clear_headers();
+    assert_eq!(d.headers().unwrap(), vec!["z", "x"]);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["6", "4"]]);
+}
+
+fn chunks(chunks: &[&str]) -> Vec<io::Result<Vec<u8>>> {
+    chunks.iter().map(|c| Ok(c.as_bytes().to_vec())).collect()
+}
+
+#[test]
+fn from_chunks_split_mid_field() {
+    let parts = chunks(&["a,b\nfo", "o,bar\n"]);
+    let mut d = Reader::from_chunks(parts.into_iter()).has_headers(false);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["foo", "bar"]]);
+}
+
+#[test]
+fn from_chunks_split_quoted_and_empty() {
+    let parts = chunks(&["\"x", "", "\"\"y\",", "\r", "\nz,w"]);
+    let mut d = Reader::from_chunks(parts.into_iter()).has_headers(false);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["x\"y", ""], vec!["z", "w"]]);
+}
+
+#[test]
+fn from_chunks_error() {
+    let parts = vec![
+        Ok(b"a,b\n".to_vec()),
+        Err(io::Error::new(io::ErrorKind::Other, "boom")),
+    ];
+    let mut d = Reader::from_chunks(parts.into_iter()).has_headers(false);
+    match d.collect_records() {
+        Err(Error::Io(_)) => {}
+        r => panic!("expected IO error, got {:?}", r),
+    }
+}
+
+#[test]
+fn header_line_crlf_split_across_chunks() {
+    let parts = chunks(&["title\r", "\nsum,1\r\nh1,h2\r\na,b\r\n"]);
+    let mut d = Reader::from_chunks(parts.into_iter()).header_line(2);
+    assert_eq!(d.headers().unwrap(), vec!["h1", "h2"]);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"]]);
+}
+
+fn config_err<R: io::Read>(rdr: Reader<R>) -> String {
+    match rdr.validate() {
+        Err(Error::Config(msg)) => msg,
+        r => panic!("expected config error, got {:?}", r),
+    }
+}
+
+#[test]
+fn validate_ok() {
+    let rdr = Reader::from_string("").delimiter(b';').quote(b'\'')
+                                     .escape(Some(b'\\'));
+    assert!(rdr.validate().is_ok());
+    let term = RecordTerminator::Any(b'|');
+    assert!(Reader::from_string("").record_terminator(term)
+                                   .validate().is_ok());
+}
+
+#[test]
+fn validate_rejects_crlf() {
+    let msg = config_err(Reader::from_string("").delimiter(b'\n'));
+    assert_eq!(msg, "delimiter '\\n' is a record terminator");
+    let msg = config_err(Reader::from_string("").quote(b'\r'));
+    assert_eq!(msg, "quote '\\r' is a record terminator");
+    let msg = config_err(Reader::from_string("").escape(Some(b'\n')));
+    assert_eq!(msg, "escape '\\n' is a record terminator");
+}
+
+#[test]
+fn validate_rejects_crlf_with_other_terminator() {
+    let term = RecordTerminator::Any(b'|');
+    config_err(Reader::from_string("").record_terminator(term)
+                                      .quote(b'\n'));
+}
+
+#[test]
+fn validate_rejects_terminator() {
+    let term = RecordTerminator::Any(b'|');
+    let msg = config_err(Reader::from_string("").record_terminator(term)
+                                                .delimiter(b'|'));
+    assert_eq!(msg, "delimiter '|' is a record terminator");
+    config_err(Reader::from_string("").record_terminator(term)
+                                      .quote(b'|'));
+    config_err(Reader::from_string("").record_terminator(term)
+                                      .escape(Some(b'|')));
+}
+
+#[test]
+fn index_every_seek() {
+    let data = "h1,h2\na,1\nb,2\nc,3\nd,4\ne,5\nf,6\ng,7\n";
+    let mut d = Reader::from_string(data);
+    let offsets = d.index_every(3).unwrap();
+    assert_eq!(offsets.len(), 3);
+    for (i, &offset) in offsets.iter().enumerate() {
+        d.seek(offset).unwrap();
+        let row = d.records().next().unwrap().unwrap();
+        assert_eq!(row[1], (i * 3 + 1).to_string());
+    }
+}
+
+#[test]
+fn index_every_exact_stride() {
+    let mut d = Reader::from_string("a\nb\nc\nd").has_headers(false);
+    assert_eq!(d.index_every(3).unwrap(), vec![0, 6]);
+    let mut d = Reader::from_string("a\nb\nc").has_headers(false);
+    assert_eq!(d.index_every(3).unwrap(), vec![0]);
+    let mut d = Reader::from_string("").has_headers(false);
+    assert_eq!(d.index_every(1).unwrap(), Vec::<u64>::new());
+}
+
+#[test]
+fn index_every_after_headers() {
+    let mut d = Reader::from_string("h\na\nb\n");
+    assert_eq!(d.headers().unwrap(), vec!["h"]);
+    assert_eq!(d.index_every(1).unwrap(), vec![2, 4]);
+}
+
+struct SeekCounter {
+    data: io::Cursor<Vec<u8>>,
+    seeks: SeekCount,
+}
+
+impl io::Read for SeekCounter {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.data.read(buf)
+    }
+}
+
+impl io::Seek for SeekCounter {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.seeks.set(self.seeks.get() + 1);
+        self.data.seek(pos)
+    }
+}
+
+type SeekCount = ::std::rc::Rc<::std::cell::Cell<usize>>;
+
+fn seek_counter(s: &str) -> (Reader<SeekCounter>, SeekCount) {
+    let seeks = SeekCount::new(::std::cell::Cell::new(0));
+    let rdr = SeekCounter {
+        data: io::Cursor::new(s.as_bytes().to_vec()),
+        seeks: seeks.clone(),
+    };
+    (Reader::from_reader(rdr).has_headers(false), seeks)
+}
+
+#[test]
+fn seek_forward_in_buffer() {
+    let (mut d, seeks) = seek_counter("a,1\nb,2\nc,3\nd,4\n");
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["a", "1"]);
+    d.seek(12).unwrap();
+    assert_eq!(seeks.get(), 0);
+    assert_eq!(d.byte_offset(), 12);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["d", "4"]]);
+}
+
+#[test]
+fn seek_forward_to_buffer_end() {
+    let (mut d, seeks) = seek_counter("a,1\nb,2\n");
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["a", "1"]);
+    d.seek(8).unwrap();
+    assert_eq!(seeks.get(), 0);
+    assert!(d.records().next().is_none());
+}
+
+#[test]
+fn seek_backward_not_in_buffer() {
+    let (mut d, seeks) = seek_counter("a,1\nb,2\nc,3\n");
+    d.records().nth(1).unwrap().unwrap();
+    d.seek(4).unwrap();
+    assert_eq!(seeks.get(), 1);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["b", "2"], vec!["c", "3"]]);
+}
+
+fn terminators(rdr: &mut Reader<io::Cursor<Vec<u8>>>) -> Vec<Vec<u8>> {
+    let mut terms = vec![];
+    while let Some(row) = rdr.byte_records().next() {
+        row.unwrap();
+        terms.push(rdr.last_terminator_bytes().to_vec());
+    }
+    terms
+}
+
+#[test]
+fn last_terminator_bytes_crlf() {
+    let mut d = Reader::from_string("a,b\r\nc,\n\"d\"\re\r\n\r\nf")
+                       .has_headers(false)
+                       .flexible(true);
+    assert_eq!(terminators(&mut d), vec![
+        b"\r\n".to_vec(), b"\n".to_vec(), b"\r".to_vec(), b"\r\n".to_vec(),
+        vec![],
+    ]);
+}
+
+#[test]
+fn last_terminator_bytes_crlf_split() {
+    let parts = chunks(&["a,b\r", "\nc,d\r", "", "\ne\r"]);
+    let mut d = Reader::from_chunks(parts.into_iter()).has_headers(false)
+                                                      .flexible(true);
+    let mut terms = vec![];
+    while let Some(row) = d.byte_records().next() {
+        row.unwrap();
+        terms.push(d.last_terminator_bytes().to_vec());
+    }
+    assert_eq!(terms, vec![
+        b"\r\n".to_vec(), b"\r\n".to_vec(), b"\r".to_vec(),
+    ]);
+}
+
+#[test]
+fn last_terminator_bytes_any() {
+    let mut d = Reader::from_string("a|b\r\n|")
+                       .has_headers(false)
+                       .record_terminator(RecordTerminator::Any(b'|'));
+    assert_eq!(terminators(&mut d), vec![b"|".to_vec(), b"|".to_vec()]);
+}
+
+#[test]
+fn last_terminator_bytes_round_trip() {
+    let data = "a,\"b\"\"c\"\r\nd,e\nf,g";
+    let mut d = Reader::from_string(data).has_headers(false)
+                                         .preserve_quotes(true);
+    let mut out = vec![];
+    while let Some(row) = d.byte_records().next() {
+        out.extend(row.unwrap().join(&b','));
+        out.extend_from_slice(d.last_terminator_bytes());
+    }
+    assert_eq!(out, data.as_bytes());
+}
+
+#[test]
+fn read_limit_on_boundary() {
+    let mut d = Reader::from_string("a,b\nc,d\ne,f\n").has_headers(false)
+                                                   .read_limit(Some(8));
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "b"], vec!["c", "d"]]);
+    assert!(d.done());
+    assert_eq!(d.byte_offset(), 8);
+}
+
+#[test]
+fn read_limit_finishes_record() {
+    let data = "a,\"long\nfield\"\nc,d\n";
+    let mut d = Reader::from_string(data).has_headers(false)
+                                         .read_limit(Some(3));
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "long\nfield"]]);
+}
+
+#[test]
+fn read_limit_seek_to_start() {
+    let mut d = Reader::from_string("h\na\nb\nc\n").read_limit(Some(6));
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["a"], vec!["b"]]);
+    d.seek_to_start().unwrap();
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["a"], vec!["b"]]);
+}
+
+fn leading_rows(data: &str) -> Vec<(String, Vec<String>)> {
+    let mut d = Reader::from_string(data).has_headers(false)
+                                         .flexible(true)
+                                         .leading_fixed(4);
+    let mut rows = vec![];
+    while let Some(row) = d.records().next() {
+        let lead = String::from_utf8(d.last_leading().to_vec()).unwrap();
+        rows.push((lead, row.unwrap()));
+    }
+    rows
+}
+
+fn leading_row(lead: &str, row: &[&str]) -> (String, Vec<String>) {
+    (lead.to_string(), row.iter().map(|s| s.to_string()).collect())
+}
+
+#[test]
+fn leading_fixed_prefix() {
+    assert_eq!(leading_rows("0012a,b\r\n0034c,\"d,e\"\n"), vec![
+        leading_row("0012", &["a", "b"]),
+        leading_row("0034", &["c", "d,e"]),
+    ]);
+}
+
+#[test]
+fn leading_fixed_special_bytes() {
+    // Delimiters and quotes in the prefix are not special.
+    assert_eq!(leading_rows("\"a,b\"x\",y"), vec![
+        leading_row("\"a,b", &["x", "y"]),
+    ]);
+}
+
+#[test]
+fn leading_fixed_short_record() {
+    assert_eq!(leading_rows("00\n\n0001a\n01"), vec![
+        leading_row("00", &[""]),
+        leading_row("0001", &["a"]),
+        leading_row("01", &[""]),
+    ]);
+}
+
+#[test]
+fn leading_fixed_only_prefix() {
+    assert_eq!(leading_rows("0001\n0002"), vec![
+        leading_row("0001", &[""]),
+        leading_row("0002", &[""]),
+    ]);
+}
+
+#[test]
+fn ascii_round_trip() {
+    let rows = vec![
+        vec!["a", "b,c", "d\ne"],
+        vec!["\"quoted\"", "", "x\x1fy"],
+    ];
+    let mut wtr = Writer::from_memory().ascii();
+    for row in &rows {
+        wtr.write(row.iter()).unwrap();
+    }
+    let data = wtr.into_bytes();
+    assert!(data.starts_with(b"a\x1fb,c\x1f"));
+    let mut rdr = Reader::from_bytes(data).has_headers(false).ascii();
+    let got = rdr.collect_records().unwrap();
+    assert_svec_eq(got, rows);
+}
+
+fn quoted_flags(data: &str) -> Vec<Vec<bool>> {
+    let mut d = Reader::from_string(data).has_headers(false)
+                                         .flexible(true)
+                                         .track_quotes(true);
+    let mut flags = vec![];
+    while let Some(row) = d.byte_records().next() {
+        row.unwrap();
+        flags.push(d.last_record_quoted().to_vec());
+    }
+    flags
+}
+
+#[test]
+fn last_record_quoted_empty_fields() {
+    assert_eq!(quoted_flags(",\"\",\n\"\",\n"), vec![
+        vec![false, true, false],
+        vec![true, false],
+    ]);
+}
+
+#[test]
+fn last_record_quoted_nonempty() {
+    assert_eq!(quoted_flags("\"a\",b\"c\",\"d\"e\n"), vec![
+        vec![true, false, true],
+    ]);
+}
+
+#[test]
+fn last_record_quoted_disabled() {
+    let mut d = Reader::from_string("\"a\",b").has_headers(false);
+    d.records().next().unwrap().unwrap();
+    assert!(d.last_record_quoted().is_empty());
+}
+
+#[test]
+fn lazy_records_valid() {
+    let mut d = Reader::from_string("h1,h2\na,βc\n");
+    let rows = d.lazy_records().collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].len(), 2);
+    assert_eq!(rows[0].get(1).unwrap().unwrap(), "βc");
+    assert_eq!(rows[0].get(0).unwrap().unwrap(), "a");
+    assert!(rows[0].get(2).is_none());
+}
+
+#[test]
+fn lazy_records_invalid_on_access() {
+    let data = &b"a,\xff\xfe,c\n"[..];
+    let mut d = Reader::from_bytes(data).has_headers(false);
+    let row = d.lazy_records().next().unwrap().unwrap();
+    assert_eq!(row.get(2).unwrap().unwrap(), "c");
+    match row.get(1).unwrap() {
+        Err(Error::Decode(_)) => {}
+        r => panic!("expected decode error, got {:?}", r),
+    }
+    // The cached result is the same.
+    assert!(row.get(1).unwrap().is_err());
+    assert_eq!(row.get_bytes(1).unwrap(), b"\xff\xfe");
+    assert_eq!(row.into_bytes(), vec![b"a".to_vec(), b"\xff\xfe".to_vec(),
+                                      b"c".to_vec()]);
+}
+
+#[test]
+fn records_with_headers_has_headers() {
+    let mut d = Reader::from_string("h1,h2\na,b\nc,d\n");
+    let rows = d.records_with_headers().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["h1", "h2"], vec!["a", "b"],
+                              vec!["c", "d"]]);
+    assert_eq!(d.headers().unwrap(), vec!["h1", "h2"]);
+}
+
+#[test]
+fn records_with_headers_no_headers() {
+    let mut d = Reader::from_string("h1,h2\na,b\n").has_headers(false);
+    let rows = d.records_with_headers().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["h1", "h2"], vec!["a", "b"]]);
+}
+
+#[test]
+fn records_with_headers_cached() {
+    let mut d = Reader::from_string("h1,h2\na,b\n");
+    assert_eq!(d.headers().unwrap(), vec!["h1", "h2"]);
+    let rows = d.records_with_headers().collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["h1", "h2"], vec!["a", "b"]]);
+}
+
+#[test]
+fn records_with_headers_empty() {
+    let mut d = Reader::from_string("");
+    assert!(d.records_with_headers().next().is_none());
+}
+
+#[test]
+fn windows2_pairs() {
+    let mut d = Reader::from_string("h\na\nb\nc\n");
+    let pairs = d.windows2().collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(pairs, vec![
+        (vec!["a".to_string()], vec!["b".to_string()]),
+        (vec!["b".to_string()], vec!["c".to_string()]),
+    ]);
+}
+
+#[test]
+fn windows2_short() {
+    let mut d = Reader::from_string("h\na\n");
+    assert!(d.windows2().next().is_none());
+    let mut d = Reader::from_string("");
+    assert!(d.windows2().next().is_none());
+}
+
+#[test]
+fn windows2_errors() {
+    let data = &b"a\n\xff\nb\n"[..];
+    let mut d = Reader::from_bytes(data).has_headers(false);
+    let pairs: Vec<_> = d.windows2().collect();
+    assert_eq!(pairs.len(), 2);
+    assert!(pairs[0].is_err());
+    let (prev, cur) = pairs[1].as_ref().unwrap().clone();
+    assert_eq!((prev, cur), (vec!["a".to_string()], vec!["b".to_string()]));
+}
+
+#[test]
+fn group_by_adjacent_keys() {
+    let data = "k,v\na,1\na,2\nb,3\na,4\na,5\n";
+    let mut d = Reader::from_string(data);
+    let groups = d.group_by(|r| r[0].clone())
+                  .collect::<Result<Vec<_>>>().unwrap();
+    let summary: Vec<(String, usize)> =
+        groups.into_iter().map(|(k, rows)| (k, rows.len())).collect();
+    assert_eq!(summary, vec![
+        ("a".to_string(), 2), ("b".to_string(), 1), ("a".to_string(), 2),
+    ]);
+}
+
+#[test]
+fn group_by_rows() {
+    let mut d = Reader::from_string("1,x\n1,y\n").has_headers(false);
+    let mut groups = d.group_by(|r| r[0].parse::<u32>().unwrap());
+    let (key, rows) = groups.next().unwrap().unwrap();
+    assert_eq!(key, 1);
+    assert_svec_eq(rows, vec![vec!["1", "x"], vec!["1", "y"]]);
+    assert!(groups.next().is_none());
+}
+
+#[test]
+fn group_by_empty() {
+    let mut d = Reader::from_string("k\n");
+    assert!(d.group_by(|r| r[0].clone()).next().is_none());
+}
+
+#[test]
+fn group_by_errors() {
+    let data = &b"a\n\xff\na\nb\n"[..];
+    let mut d = Reader::from_bytes(data).has_headers(false);
+    let groups: Vec<_> = d.group_by(|r| r[0].clone()).collect();
+    assert_eq!(groups.len(), 3);
+    assert!(groups[0].is_err());
+    assert_eq!(groups[1].as_ref().unwrap().1.len(), 2);
+    assert_eq!(groups[2].as_ref().unwrap().0, "b");
+}
+
+fn detect(sample: &str) -> bool {
+    Reader::from_string("").detect_headers(sample.as_bytes())
+}
+
+#[test]
+fn detect_headers_numeric_column() {
+    assert!(detect("name,age\nAda,36\nAlan,41\n"));
+    assert!(detect("id,price\n1,2.5\n2,-3e2\n3,\n"));
+}
+
+#[test]
+fn detect_headers_no_header() {
+    assert!(!detect("Ada,36\nAlan,41\n"));
+    assert!(!detect("a,b\nc,d\ne,f\n"));
+    assert!(!detect("year,2019\nx,1\n"));
+}
+
+#[test]
+fn detect_headers_short_sample() {
+    assert!(!detect("name,age\n"));
+    assert!(!detect(""));
+    // The cut short last row is ignored.
+    assert!(!detect("name,age\nAda,3"));
+    assert!(detect("name,age\nAda,36\nAl"));
+}
+
+#[test]
+fn detect_headers_dialect() {
+    let rdr = Reader::from_string("").delimiter(b'\t');
+    assert!(rdr.detect_headers(b"a\tb\n\"x\"\t1\n"));
+    assert!(!rdr.detect_headers(b"a,b\nx,1\n"));
+    // Words that parse as floats aren't numbers.
+    assert!(detect("nan,inf\nx,1\n"));
+}
+
+#[test]
+fn assert_eof_whitespace() {
+    let mut d = Reader::from_string("a,b\r\n1,2\r\n\r\n \t\r\n\x0c\n");
+    assert_eq!(d.records().take(1).count(), 1);
+    d.assert_eof().unwrap();
+    assert!(d.done());
+    assert!(d.records().next().is_none());
+}
+
+#[test]
+fn assert_eof_trailing_record() {
+    let mut d = Reader::from_string("a,b\n1,2\n\n  x,y\n");
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["1", "2"]);
+    match d.assert_eof().unwrap_err() {
+        Error::Parse(LocatableError {
+            err: ParseError::TrailingData { pos: 11 }, ..
+        }) => {}
+        err => panic!("unexpected error: {:?}", err),
+    }
+    // The trailing data can still be read.
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["  x", "y"]]);
+}
+
+#[test]
+fn assert_eof_comments() {
+    let mut d = Reader::from_string("a,b\n1,2\n#done\n \n#x,y")
+                       .comment(Some(b'#'));
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["1", "2"]);
+    d.assert_eof().unwrap();
+    assert!(d.done());
+
+    let mut d = Reader::from_string("a,b\n1,2\n #x\n").comment(Some(b'#'));
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["1", "2"]);
+    match d.assert_eof().unwrap_err() {
+        Error::Parse(LocatableError {
+            err: ParseError::TrailingData { pos: 9 }, ..
+        }) => {}
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[test]
+fn assert_eof_peeked() {
+    let mut d = Reader::from_string("a\nb\n").has_headers(false);
+    assert_eq!(d.peek_field_count().unwrap(), Some(1));
+    assert!(d.assert_eof().is_err());
+}
+
+#[test]
+fn assert_eof_mid_record() {
+    let mut d = Reader::from_string("a,b\n").has_headers(false);
+    d.next_bytes().into_iter_result().unwrap().unwrap();
+    assert!(d.assert_eof().is_err());
+}
+
+fn aligned(data: &str, pos: u64) -> (u64, Vec<Vec<String>>) {
+    let mut d = Reader::from_reader(io::Cursor::new(data.as_bytes()))
+                       .has_headers(false);
+    let landed = d.seek_aligned(pos).unwrap();
+    (landed, d.decode().collect::<Result<Vec<Vec<String>>>>().unwrap())
+}
+
+#[test]
+fn seek_aligned_mid_record() {
+    let (pos, rows) = aligned("abc,xyz\nfoo,bar\nbaz,quux\n", 10);
+    assert_eq!(pos, 16);
+    assert_eq!(rows, vec![vec!["baz".to_owned(), "quux".to_owned()]]);
+}
+
+#[test]
+fn seek_aligned_at_boundary() {
+    let (pos, rows) = aligned("abc,xyz\nfoo,bar\n", 8);
+    assert_eq!(pos, 8);
+    assert_eq!(rows, vec![vec!["foo".to_owned(), "bar".to_owned()]]);
+    let (pos, _) = aligned("abc,xyz\nfoo,bar\n", 0);
+    assert_eq!(pos, 0);
+}
+
+#[test]
+fn seek_aligned_crlf() {
+    let (pos, rows) = aligned("abc,xyz\r\nfoo,bar\r\n", 8);
+    assert_eq!(pos, 9);
+    assert_eq!(rows, vec![vec!["foo".to_owned(), "bar".to_owned()]]);
+    let (pos, _) = aligned("abc,xyz\r\nfoo,bar\r\n", 9);
+    assert_eq!(pos, 9);
+}
+
+#[test]
+fn seek_aligned_no_boundary() {
+    let (pos, rows) = aligned("abc,xyz\nfoo,bar", 10);
+    assert_eq!(pos, 15);
+    assert!(rows.is_empty());
+}
+
+#[test]
+fn decode_tuple_arity() {
+    let data = "a,1,1.5\nb,2\nc,3,3.5,x\n";
+    let mut d = Reader::from_string(data).has_headers(false).flexible(true);
+    let rows: Vec<Result<(String, u32, f64)>> = d.decode().collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0].as_ref().unwrap(), &("a".to_owned(), 1, 1.5));
+    match rows[1] {
+        Err(Error::Decode(_)) => {}
+        ref r => panic!("expected a decode error, got {:?}", r),
+    }
+    match rows[2] {
+        Err(Error::Decode(ref msg)) => {
+            assert_eq!(msg, "Tuple has 3 fields but current record \
+                             has 4 fields.");
+        }
+        ref r => panic!("expected a decode error, got {:?}", r),
+    }
+}
+
+#[test]
+fn null_value_option() {
+    let data = "a,b\n1,\\N\n2,3\n\\N,\n";
+    let mut d = Reader::from_string(data).null_value("\\N");
+    let rows: Vec<(Option<i32>, Option<i32>)> =
+        d.decode().collect::<Result<_>>().unwrap();
+    assert_eq!(rows, vec![(Some(1), None), (Some(2), Some(3)), (None, None)]);
+}
+
+#[test]
+fn null_value_required() {
+    let data = "a,b\n1,NULL\n";
+    let mut d = Reader::from_string(data).null_value("NULL");
+    let rows: Vec<Result<(i32, String)>> = d.decode().collect();
+    match rows[0] {
+        Err(Error::Decode(ref msg)) => assert!(msg.contains("NULL")),
+        ref r => panic!("expected a decode error, got {:?}", r),
+    }
+}
+
+#[test]
+fn null_value_raw_records() {
+    let mut d = Reader::from_string("a\n\\N\n").null_value("\\N");
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["\\N"]);
+}
+
+#[test]
+fn capture_raw_unequal_lengths() {
+    let mut d = Reader::from_string("a,b\n1,\"x,y\",3\n4,5\n")
+                       .capture_raw(true);
+    match d.records().next() {
+        Some(Err(Error::Parse(LocatableError {
+            record: 2,
+            err: ParseError::UnequalLengths { expected: 2, got: 3 },
+            ..
+        }))) => {}
+        r => panic!("expected an unequal lengths error, got {:?}", r),
+    }
+    assert_eq!(d.last_byte_record().unwrap(),
+               &[b"1".to_vec(), b"x,y".to_vec(), b"3".to_vec()]);
+    assert_eq!(d.last_raw_bytes(), Some(&b"1,\"x,y\",3\n"[..]));
+}
+
+#[test]
+fn capture_raw_disabled() {
+    let mut d = Reader::from_string("a,b\n1\n");
+    assert!(d.records().next().unwrap().is_err());
+    assert!(d.last_byte_record().is_none());
+}
+
+#[test]
+fn progress_counter() {
+    use std::sync::atomic::Ordering;
+
+    let mut d = Reader::from_string("a,b\n1,2\n3,4").has_headers(false);
+    assert_eq!(d.next_str().into_iter_result().unwrap().unwrap(), "a");
+    let progress = d.progress_counter();
+    assert_eq!(progress.load(Ordering::Relaxed), 2);
+    assert_eq!(d.next_str().into_iter_result().unwrap().unwrap(), "b");
+    assert!(d.next_str().into_iter_result().is_none());
+    assert_eq!(progress.load(Ordering::Relaxed), 4);
+    let progress2 = d.progress_counter();
+    while !d.next_bytes().is_end() {}
+    assert_eq!(progress.load(Ordering::Relaxed), 11);
+    assert_eq!(progress2.load(Ordering::Relaxed), 11);
+}
+
+#[test]
+fn progress_counter_seek() {
+    use std::sync::atomic::Ordering;
+
+    let mut d = Reader::from_string("a,b\n1,2\n");
+    let progress = d.progress_counter();
+    d.collect_records().unwrap();
+    d.seek_to_start().unwrap();
+    d.collect_records().unwrap();
+    assert_eq!(progress.load(Ordering::Relaxed), 16);
+}
+
+parses_to!(quote_pair_brackets, "[a,b],c", vec![vec!["a,b", "c"]],
+           |rdr: Reader<_>| rdr.quote_pair(b'[', b']'));
+parses_to!(quote_pair_doubled_close, "[a]]b],[[c]", vec![vec!["a]b", "[c"]],
+           |rdr: Reader<_>| rdr.quote_pair(b'[', b']'));
+parses_to!(quote_pair_newline, "[a\nb]\n\"c\"", vec![
+    vec!["a\nb"], vec!["\"c\""],
+], |rdr: Reader<_>| rdr.quote_pair(b'[', b']').flexible(true));
+parses_to!(quote_pair_escape, "[a\\]b]", vec![vec!["a]b"]],
+           |rdr: Reader<_>| rdr.quote_pair(b'[', b']').escape(Some(b'\\')));
+parses_to!(quote_pair_reset, "\"a,b\"", vec![vec!["a,b"]],
+           |rdr: Reader<_>| rdr.quote_pair(b'[', b']').quote(b'"'));
+
+#[test]
+fn stats_records() {
+    let data = "a,b\n\"x,y\",1\n2,3\r\nlast,4";
+    let mut d = Reader::from_string(data);
+    let stats = d.stats().unwrap();
+    assert_eq!(stats, ReaderStats {
+        records: 3,
+        total_bytes: 19,
+        min_record_bytes: 5,
+        max_record_bytes: 8,
+        mean_record_bytes: 19.0 / 3.0,
+        max_fields: 2,
+    });
+    assert!(d.done());
+}
+
+#[test]
+fn stats_empty() {
+    let mut d = Reader::from_string("a,b\n");
+    assert_eq!(d.stats().unwrap(), ReaderStats::default());
+}
+
+#[test]
+fn stats_error() {
+    let mut d = Reader::from_string("a,b\n1,2,3\n");
+    assert!(d.stats().is_err());
+}
+
+#[test]
+fn unread_record_again() {
+    let mut d = Reader::from_string("a,b\n1,2\n3,4\n");
+    let first = d.records().next().unwrap().unwrap();
+    let second = d.records().next().unwrap().unwrap();
+    assert_eq!(second, vec!["3", "4"]);
+    d.unread_record(second).unwrap();
+    assert_eq!(d.unread_record(first).unwrap_err(), vec!["1", "2"]);
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["3", "4"]);
+    assert!(d.records().next().is_none());
+}
+
+#[test]
+fn unread_byte_record_peeked() {
+    let mut d = Reader::from_string("a\n1\n2\n");
+    let row = d.byte_records().next().unwrap().unwrap();
+    assert_eq!(d.peek_field_count().unwrap(), Some(1));
+    assert!(d.unread_byte_record(row).is_err());
+    let rows = d.byte_records().collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(rows, vec![vec![b"2".to_vec()]]);
+}
+
+#[test]
+fn unread_record_no_headers() {
+    let mut d = Reader::from_string("a\n1\n").has_headers(false);
+    d.unread_record(vec!["x".to_owned()]).unwrap();
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["x"], vec!["a"], vec!["1"]]);
+}
+
+parses_to!(term_none, "a,b\nc,\"d\ne\",\r\nf\n", vec![
+    vec!["a", "b\nc", "d\ne", "\r\nf\n"],
+], |rdr: Reader<_>| rdr.record_terminator(RecordTerminator::None));
+parses_to!(term_none_empty_fields, "\n,\n", vec![vec!["\n", "\n"]],
+           |rdr: Reader<_>| rdr.record_terminator(RecordTerminator::None));
+writes_as!(wtr_term_none, vec![vec!["a", "b"]], "a,b",
+           |wtr: Writer<_>| wtr.record_terminator(RecordTerminator::None));
+
+#[test]
+fn wtr_term_none_second_record() {
+    let mut wtr = Writer::from_memory()
+                         .record_terminator(RecordTerminator::None);
+    wtr.write(vec!["a", "b"].into_iter()).unwrap();
+    assert!(wtr.write(vec!["c", "d"].into_iter()).is_err());
+    assert_eq!(wtr.as_string(), "a,b");
+}
+
+#[test]
+fn term_none_headers() {
+    let mut d = Reader::from_string("a,b\nc")
+                       .record_terminator(RecordTerminator::None);
+    assert_eq!(d.headers().unwrap(), vec!["a", "b\nc"]);
+    assert!(d.collect_records().unwrap().is_empty());
+}
+
+fn single_column<R: io::Read>(mut rdr: Reader<R>) -> Vec<Result<String>> {
+    let mut rows = vec![];
+    let mut buf = String::new();
+    loop {
+        match rdr.read_single_column(&mut buf) {
+            Ok(false) => return rows,
+            Ok(true) => rows.push(Ok(buf.clone())),
+            Err(err) => rows.push(Err(err)),
+        }
+    }
+}
+
+#[test]
+fn single_column_quoted() {
+    let rows = single_column(Reader::from_string("a\n\"x\ny\"\n\"\"\nz"));
+    let rows = rows.into_iter().collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(rows, vec!["x\ny", "", "z"]);
+}
+
+#[test]
+fn single_column_no_headers() {
+    let rdr = Reader::from_string("a\nb\n").has_headers(false);
+    let rows = single_column(rdr);
+    let rows = rows.into_iter().collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(rows, vec!["a", "b"]);
+}
+
+#[test]
+fn single_column_too_many_fields() {
+    let rdr = Reader::from_string("a\nb\nc,d,e\nf\n").flexible(true);
+    let rows = single_column(rdr);
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0].as_ref().unwrap(), "b");
+    match rows[1] {
+        Err(Error::Parse(LocatableError {
+            record: 3,
+            err: ParseError::UnequalLengths { expected: 1, got: 3, .. },
+            ..
+        })) => {}
+        ref r => panic!("expected an unequal lengths error, got {:?}", r),
+    }
+    assert_eq!(rows[2].as_ref().unwrap(), "f");
+}
+
+#[test]
+fn single_column_peeked() {
+    let mut rdr = Reader::from_string("a\nb\nc\n");
+    assert_eq!(rdr.peek_field_count().unwrap(), Some(1));
+    let rows = single_column(rdr);
+    let rows = rows.into_iter().collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(rows, vec!["b", "c"]);
+}
+
+encodes_as!(encode_none_empty, vec![(1, None::<i32>)], "1,\n");
+encodes_as!(encode_none_null_value,
+            vec![(Some(1), None::<i32>), (None, Some(2))], "1,NULL\nNULL,2\n",
+            |wtr: Writer<_>| wtr.null_value("NULL"));
+
+#[test]
+fn null_value_round_trip() {
+    let rows = vec![
+        (Some(1), None, "a".to_owned()),
+        (None, Some(2.5), "".to_owned()),
+    ];
+    let mut wtr = Writer::from_memory().null_value("\\N");
+    for row in rows.iter() {
+        wtr.encode(row).unwrap();
+    }
+    let data = wtr.as_string().to_owned();
+    assert_eq!(data, "1,\\N,a\n\\N,2.5,\n");
+
+    let mut rdr = Reader::from_string(data).has_headers(false)
+                                           .null_value("\\N");
+    let got: Vec<(Option<i32>, Option<f64>, String)> =
+        rdr.decode().collect::<Result<_>>().unwrap();
+    assert_eq!(got, rows);
+}
+
+#[test]
+fn looks_delimited_mostly() {
+    let data = b"name|age\r\nAda|36\r\n\r\nremark without delimiter\r\n";
+    assert!(looks_delimited(data, b'|'));
+    assert!(!looks_delimited(data, b','));
+}
+
+#[test]
+fn looks_delimited_fixed_width() {
+    let data = b"name  age\nAda    36\nAlan   41\nGrace, 85\n";
+    assert!(!looks_delimited(data, b','));
+    assert!(!looks_delimited(b"", b','));
+    assert!(!looks_delimited(b"\n\n", b','));
+}
+
+#[test]
+fn last_record_again() {
+    let mut d = Reader::from_string("a,b\n1,\"x,y\"\n3,4\n")
+                       .capture_raw(true);
+    assert!(d.last_record().is_none());
+    let row = d.records().next().unwrap().unwrap();
+    assert_eq!(d.last_record().unwrap().unwrap(), row);
+    assert_eq!(d.last_byte_record().unwrap(), &[b"1".to_vec(),
+                                                 b"x,y".to_vec()][..]);
+    d.seek_to_start().unwrap();
+    assert!(d.last_byte_record().is_none());
+}
+
+#[test]
+fn last_record_disabled() {
+    let mut d = Reader::from_string("a,b\n1,2\n");
+    d.records().next().unwrap().unwrap();
+    assert!(d.last_byte_record().is_none());
+}
+
+#[test]
+fn last_record_partial() {
+    let mut d = Reader::from_string("a,b\n1,2\n").capture_raw(true)
+                       .has_headers(false);
+    d.next_bytes().into_iter_result().unwrap().unwrap();
+    assert!(d.last_byte_record().is_none());
+    d.next_bytes().into_iter_result().unwrap().unwrap();
+    assert!(d.next_bytes().into_iter_result().is_none());
+    assert_eq!(d.last_record().unwrap().unwrap(), vec!["a", "b"]);
+}
+
+fn sink_counter() -> (::std::sync::Arc<::std::sync::Mutex<Vec<String>>>,
+                      Box<FnMut(&Error) + Send>) {
+    use std::sync::{Arc, Mutex};
+
+    let log = Arc::new(Mutex::new(vec![]));
+    let log2 = log.clone();
+    (log, Box::new(move |err: &Error| {
+        log2.lock().unwrap().push(err.to_string());
+    }))
+}
+
+#[test]
+fn error_sink_two_bad_rows() {
+    let (log, mut sink) = sink_counter();
+    let data = &b"a,b\n1,2\n3\n\xff,4\n5,6\n"[..];
+    let mut d = Reader::from_bytes(data).error_sink(move |e| sink(e));
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["1", "2"], vec!["5", "6"]]);
+    let log = log.lock().unwrap();
+    assert_eq!(log.len(), 2);
+    assert!(log[0].contains("length 1"), "{}", log[0]);
+    assert!(log[1].contains("UTF-8"), "{}", log[1]);
+}
+
+#[test]
+fn error_sink_byte_records() {
+    let (log, mut sink) = sink_counter();
+    let data = &b"a,b\n1\n\xff,4\n"[..];
+    let mut d = Reader::from_bytes(data).error_sink(move |e| sink(e));
+    let rows = d.byte_records().collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(rows, vec![vec![b"\xff".to_vec(), b"4".to_vec()]]);
+    assert_eq!(log.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn error_sink_fatal() {
+    let (log, mut sink) = sink_counter();
+    let mut d = Reader::from_string("a,b\n1,2,3,4\n")
+                       .max_fields(Some(3))
+                       .error_sink(move |e| sink(e));
+    assert!(d.collect_records().is_err());
+    assert!(log.lock().unwrap().is_empty());
+}
+
+#[test]
+fn error_sink_take_records_while() {
+    let (log, mut sink) = sink_counter();
+    let data = &b"a,b\n1\n\xff,4\n5,6\n,\n"[..];
+    let mut d = Reader::from_bytes(data).error_sink(move |e| sink(e));
+    let rows = d.take_records_while(|r| r[0] != "")
+                .collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["5", "6"]]);
+    assert_eq!(log.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn error_sink_into_records_with_headers() {
+    let (log, mut sink) = sink_counter();
+    let data = &b"a,b\n1\n\xff,4\n5,6\n"[..];
+    let d = Reader::from_bytes(data).error_sink(move |e| sink(e));
+    let (headers, it) = d.into_records_with_headers().unwrap();
+    assert_eq!(headers, vec!["a", "b"]);
+    let rows = it.collect::<Result<Vec<_>>>().unwrap();
+    assert_svec_eq(rows, vec![vec!["5", "6"]]);
+    assert_eq!(log.lock().unwrap().len(), 2);
+}
+
+parses_to!(comment_lines, "#x\na,b\n#y,z\n1,2\n#\n#last", vec![
+    vec!["a", "b"], vec!["1", "2"],
+], |rdr: Reader<_>| rdr.comment(Some(b'#')));
+parses_to!(comment_not_first_byte, " #x\na#b,\"#c\"", vec![
+    vec![" #x"], vec!["a#b", "#c"],
+], |rdr: Reader<_>| rdr.comment(Some(b'#')).flexible(true));
+parses_to!(comment_disabled, "#x\n", vec![vec!["#x"]]);
+
+#[test]
+fn comment_everywhere() {
+    let data = "# header follows\r\nh1,h2\r\n#c1\r\na,1\r\n# c2\r\nb,2\r\n";
+    let rdr = || Reader::from_string(data).comment(Some(b'#'));
+
+    assert_eq!(rdr().headers().unwrap(), vec!["h1", "h2"]);
+    assert_eq!(rdr().records().count(), 2);
+    assert_eq!(rdr().stats().unwrap().records, 2);
+    assert_eq!(rdr().index_every(1).unwrap().len(), 2);
+    assert_eq!(rdr().skip_records_while(|_| true).unwrap(), 2);
+    let rows = rdr().has_headers(false).collect_records().unwrap();
+    assert_eq!(rows.len(), 3);
+}
+
+#[test]
+fn comment_stats_bytes() {
+    // A comment line counts towards the size of the record after it.
+    let mut d = Reader::from_string("a,b\n#c\n1,2\n").comment(Some(b'#'));
+    let stats = d.stats().unwrap();
+    assert_eq!(stats.records, 1);
+    assert_eq!(stats.max_record_bytes, 7);
+}
+
+#[test]
+fn comment_conflict() {
+    let msg = config_err(Reader::from_string("").comment(Some(b'\n')));
+    assert!(msg.contains("comment"), "{}", msg);
+}
+
+#[test]
+fn partial_field_count() {
+    let mut d = Reader::from_string("a,b,c\n1,2,3\n").has_headers(false);
+    assert_eq!(d.partial_field_count(), 0);
+    d.next_bytes().into_iter_result().unwrap().unwrap();
+    d.next_bytes().into_iter_result().unwrap().unwrap();
+    assert_eq!(d.partial_field_count(), 2);
+    d.next_bytes().into_iter_result().unwrap().unwrap();
+    assert!(d.next_bytes().into_iter_result().is_none());
+    assert_eq!(d.partial_field_count(), 0);
+
+    let mut d = Reader::from_string("a,b\n1,2,3,4,5\n").max_fields(Some(3))
+                       .flexible(true);
+    assert!(d.records().next().unwrap().is_err());
+    assert_eq!(d.partial_field_count(), 3);
+}
+
+fn column(name: Option<&str>, kind: ColumnKind, nullable: bool) -> ColumnType {
+    ColumnType {
+        name: name.map(|s| s.to_owned()),
+        kind: kind,
+        nullable: nullable,
+    }
+}
+
+#[test]
+fn infer_schema_kinds() {
+    let data = "i,f,b,t,mixed,empty\n\
+                1,1,true,x,1,\n\
+                -2, 2.5 ,false,y,true,\n\
+                3,1e3,true,1,2,\n";
+    let mut d = Reader::from_string(data);
+    assert_eq!(d.infer_schema(10).unwrap(), vec![
+        column(Some("i"), ColumnKind::Int, false),
+        column(Some("f"), ColumnKind::Float, false),
+        column(Some("b"), ColumnKind::Bool, false),
+        column(Some("t"), ColumnKind::Text, false),
+        column(Some("mixed"), ColumnKind::Text, false),
+        column(Some("empty"), ColumnKind::Text, true),
+    ]);
+}
+
+#[test]
+fn infer_schema_max_rows() {
+    let mut d = Reader::from_string("a\n1\nx\n2\n");
+    assert_eq!(d.infer_schema(1).unwrap(),
+               vec![column(Some("a"), ColumnKind::Int, false)]);
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["x"]);
+}
+
+#[test]
+fn infer_schema_flexible_no_headers() {
+    let mut d = Reader::from_string("1\n2,NaN\n,3.5\n")
+                       .has_headers(false)
+                       .flexible(true);
+    assert_eq!(d.infer_schema(10).unwrap(), vec![
+        column(None, ColumnKind::Int, true),
+        column(None, ColumnKind::Text, true),
+    ]);
+}
+
+#[test]
+fn add_column_sources() {
+    let data = "a,b\n\n1,2\n\"x\ny\",4\n";
+    let mut d = Reader::from_string(data)
+        .add_column("src", ColumnSource::Value("f.csv".to_owned()))
+        .add_column("n", ColumnSource::RecordNumber)
+        .add_column("pos", ColumnSource::ByteOffset)
+        .add_column("len", ColumnSource::With(Box::new(|r| {
+            r[0].len().to_string().into_bytes()
+        })));
+    assert_eq!(d.headers().unwrap(),
+               vec!["a", "b", "src", "n", "pos", "len"]);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![
+        vec!["1", "2", "f.csv", "2", "5", "1"],
+        vec!["x\ny", "4", "f.csv", "3", "9", "3"],
+    ]);
+}
+
+#[test]
+fn add_column_no_headers() {
+    let mut d = Reader::from_string("a\nb\n").has_headers(false)
+                       .add_column("pos", ColumnSource::ByteOffset);
+    assert_eq!(d.headers().unwrap(), vec!["a", "0"]);
+    assert_eq!(d.peek_field_count().unwrap(), Some(2));
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["a", "0"], vec!["b", "2"]]);
+}
+
+#[test]
+fn add_column_not_counted() {
+    let mut d = Reader::from_string("a,b\n1,2\n3\n")
+                       .project(vec![1])
+                       .add_column("n", ColumnSource::RecordNumber);
+    assert_eq!(d.headers().unwrap(), vec!["b", "n"]);
+    let mut rows = d.records();
+    assert_eq!(rows.next().unwrap().unwrap(), vec!["2", "2"]);
+    assert!(rows.next().unwrap().is_err());
+}
+
+#[test]
+fn add_column_clear_headers_read_ahead() {
+    let mut d = Reader::from_string("a,b\n1,2\nx,y\n3,4\n")
+                       .add_column("n", ColumnSource::RecordNumber);
+    assert_eq!(d.records().next().unwrap().unwrap(), vec!["1", "2", "2"]);
+    assert_eq!(d.peek_field_count().unwrap(), Some(3));
+    d.clear_headers();
+    assert_eq!(d.headers().unwrap(), vec!["x", "y", "n"]);
+    assert_eq!(d.peek_headers().unwrap(), &[b"x".to_vec(), b"y".to_vec()]);
+    let rows = d.collect_records().unwrap();
+    assert_svec_eq(rows, vec![vec!["3", "4", "4"]]);
+}
+
+#[test]
+fn clear_headers_after_seek() {
+    let data = "a,b\n1,2\n";
+    let mut d = Reader::from_reader(io::Cursor::new(data.as_bytes()));
+    assert_eq!(d.collect_records().unwrap(), vec![vec!["1", "2"]]);
+
+    d.seek(0).unwrap();
+    d.clear_headers();
+    assert_eq!(d.headers().unwrap(), vec!["a", "b"]);
+    assert_eq!(d.collect_records().unwrap(), vec![vec!["1", "2"]]);
+
+    // Without `clear_headers`, the header row is read as a record.
+    d.seek(0).unwrap();
+    assert_eq!(d.collect_records().unwrap(),
+               vec![vec!["a", "b"], vec!["1", "2"]]);
+}
+
+parses_to!(alt_quote_mixed, "\"a,b\",'c,d',e\n'x\"y','it''s',\"\"\"\"",
+           vec![vec!["a,b", "c,d", "e"], vec!["x\"y", "it's", "\""]],
+           |rdr: Reader<_>| rdr.alt_quote(Some(b'\'')));
+parses_to!(alt_quote_other_inside, "\"it's\",'say \"hi\"'",
+           vec![vec!["it's", "say \"hi\""]],
+           |rdr: Reader<_>| rdr.alt_quote(Some(b'\'')));
+parses_to!(alt_quote_disabled, "'a,b'", vec![vec!["'a", "b'"]]);
+parses_to!(alt_quote_escape, "'a\\'b',\"c\\\"d\"",
+           vec![vec!["a'b", "c\"d"]],
+           |rdr: Reader<_>| rdr.alt_quote(Some(b'\''))
+                               .escape(Some(b'\\')));
+parses_to!(alt_quote_pair, "[a'],']b'", vec![vec!["a'", "]b"]],
+           |rdr: Reader<_>| rdr.quote_pair(b'[', b']')
+                               .alt_quote(Some(b'\'')));
+
+#[test]
+fn last_record_quoted_projected() {
+    let mut d = Reader::from_string("\"a\",b\n\"c\",\"d\"\n")
+                       .has_headers(false)
+                       .project(vec![1, 2])
+                       .add_column("n", ColumnSource::RecordNumber)
+                       .track_quotes(true);
+    let mut flags = vec![];
+    while let Some(row) = d.records().next() {
+        let row = row.unwrap();
+        assert_eq!(d.last_record_quoted().len(), row.len());
+        flags.push(d.last_record_quoted().to_vec());
+    }
+    assert_eq!(flags, vec![
+        vec![false, false, false], vec![true, false, false],
+    ]);
+}
+
+#[test]
+fn alt_quote_tracked() {
+    let mut d = Reader::from_string("'a',b").has_headers(false)
+                       .alt_quote(Some(b'\''))
+                       .track_quotes(true);
+    d.collect_records().unwrap();
+    assert_eq!(d.last_record_quoted(), &[true, false]);
+}
@@ -0,0 +1,180 @@
+use std::char;
+use std::io;
+
+/// A reader that transcodes UTF-16 data to UTF-8.
+///
+/// The encoding is detected from a byte order mark (BOM) at the start of the
+/// data: `FF FE` for little endian and `FE FF` for big endian. The BOM is
+/// removed. Data without a UTF-16 BOM is passed through unchanged.
+///
+/// Invalid UTF-16 (e.g., an unpaired surrogate) is replaced with
+/// `U+FFFD REPLACEMENT CHARACTER`.
+///
+/// This is created by `Reader::from_reader_utf16`.
+pub struct Utf16Transcoder<R> {
+    rdr: R,
+    mode: Mode,
+    // Bytes read but not yet transcoded.
+    inbuf: Vec<u8>,
+    // Bytes transcoded but not yet returned.
+    outbuf: Vec<u8>,
+    outpos: usize,
+    eof: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Unknown,
+    Passthrough,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl<R: io::Read> Utf16Transcoder<R> {
+    /// Creates a new transcoder for the reader given.
+    pub fn new(rdr: R) -> Utf16Transcoder<R> {
+        Utf16Transcoder {
+            rdr: rdr,
+            mode: Mode::Unknown,
+            inbuf: vec![],
+            outbuf: vec![],
+            outpos: 0,
+            eof: false,
+        }
+    }
+
+    /// Reads more bytes into `inbuf`, and records whether EOF was reached.
+    fn fill(&mut self) -> io::Result<()> {
+        let mut chunk = [0; 8 * (1 << 10)];
+        let n = try!(self.rdr.read(&mut chunk));
+        if n == 0 {
+            self.eof = true;
+        }
+        self.inbuf.extend_from_slice(&chunk[..n]);
+        Ok(())
+    }
+
+    /// Determines the encoding from the first two bytes.
+    ///
+    /// The BOM may be split over several reads, so this reads until there
+    /// are at least two bytes (or the data ends).
+    fn detect(&mut self) -> io::Result<()> {
+        while self.inbuf.len() < 2 && !self.eof {
+            try!(self.fill());
+        }
+        self.mode = match (self.inbuf.first(), self.inbuf.get(1)) {
+            (Some(&0xFF), Some(&0xFE)) => Mode::Utf16Le,
+            (Some(&0xFE), Some(&0xFF)) => Mode::Utf16Be,
+            _ => Mode::Passthrough,
+        };
+        if self.mode != Mode::Passthrough {
+            self.inbuf.drain(..2);
+        }
+        Ok(())
+    }
+
+    /// Transcodes as much of `inbuf` as possible into `outbuf`.
+    fn transcode(&mut self) {
+        let mut units = Vec::with_capacity(self.inbuf.len() / 2);
+        for pair in self.inbuf.chunks(2) {
+            if pair.len() < 2 {
+                break;
+            }
+            units.push(match self.mode {
+                Mode::Utf16Le => (pair[1] as u16) << 8 | pair[0] as u16,
+                _ => (pair[0] as u16) << 8 | pair[1] as u16,
+            });
+        }
+        // A leading surrogate (0xD800 to 0xDBFF) may be completed by the next
+        // read.
+        let mut used = units.len() * 2;
+        if !self.eof {
+            if let Some(&last) = units.last() {
+                if last & 0xFC00 == 0xD800 {
+                    units.pop();
+                    used -= 2;
+                }
+            }
+        }
+        let mut s = String::with_capacity(units.len());
+        for c in char::decode_utf16(units) {
+            s.push(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+        }
+        self.inbuf.drain(..used);
+        if self.eof && !self.inbuf.is_empty() {
+            // An odd number of bytes, so the last one is garbage.
+            s.push(char::REPLACEMENT_CHARACTER);
+            self.inbuf.clear();
+        }
+        self.outbuf = s.into_bytes();
+        self.outpos = 0;
+    }
+}
+
+impl<R: io::Read> io::Read for Utf16Transcoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.mode == Mode::Unknown {
+            try!(self.detect());
+        }
+        if self.mode == Mode::Passthrough {
+            if self.inbuf.is_empty() {
+                return self.rdr.read(buf);
+            }
+            let n = ::std::cmp::min(buf.len(), self.inbuf.len());
+            buf[..n].copy_from_slice(&self.inbuf[..n]);
+            self.inbuf.drain(..n);
+            return Ok(n);
+        }
+        while self.outpos == self.outbuf.len() {
+            if self.eof && self.inbuf.is_empty() {
+                return Ok(0);
+            }
+            if !self.eof {
+                try!(self.fill());
+            }
+            self.transcode();
+        }
+        let n = ::std::cmp::min(buf.len(), self.outbuf.len() - self.outpos);
+        buf[..n].copy_from_slice(&self.outbuf[self.outpos..self.outpos + n]);
+        self.outpos += n;
+        Ok(n)
+    }
+}
+
+/// A reader over a sequence of byte chunks.
+///
+/// Each chunk is read in full before the next one is requested. Empty
+/// chunks are skipped. An error yielded by the iterator is returned from
+/// `read`, and reading may continue with the next chunk afterwards.
+///
+/// This is created by `Reader::from_chunks`.
+pub struct ChunkReader<I> {
+    chunks: I,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl<I: Iterator<Item=io::Result<Vec<u8>>>> ChunkReader<I> {
+    /// Creates a new reader over the chunks given.
+    pub fn new(chunks: I) -> ChunkReader<I> {
+        ChunkReader { chunks: chunks, chunk: vec![], pos: 0 }
+    }
+}
+
+impl<I: Iterator<Item=io::Result<Vec<u8>>>> io::Read for ChunkReader<I> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.chunks.next() {
+                None => return Ok(0),
+                Some(chunk) => {
+                    self.chunk = try!(chunk);
+                    self.pos = 0;
+                }
+            }
+        }
+        let n = ::std::cmp::min(buf.len(), self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
@@ -7,7 +7,8 @@ use memchr::memchr;
 use rustc_serialize::Encodable;
 
 use {
-    BorrowBytes, ByteString, Result, Encoded, Error, RecordTerminator,
+    BorrowBytes, ByteString, Result, Encoded, Error, FloatFormat,
+    RecordTerminator,
 };
 
 /// The quoting style to use when writing CSV data.
@@ -74,7 +75,10 @@ pub struct Writer<W: Write> {
     escape: u8,
     double_quote: bool,
     quote_style: QuoteStyle,
+    float_format: FloatFormat,
+    null_value: Option<ByteString>,
     first_len: usize,
+    wrote_record: bool,
 }
 
 impl Writer<fs::File> {
@@ -111,7 +115,10 @@ impl<W: io::Write> Writer<W> {
             escape: b'\\',
             double_quote: true,
             quote_style: QuoteStyle::Necessary,
+            float_format: FloatFormat::Trimmed,
+            null_value: None,
             first_len: 0,
+            wrote_record: false,
         }
     }
 }
@@ -206,7 +213,10 @@ impl<W: io::Write> Writer<W> {
     /// # }
     /// ```
     pub fn encode<E>(&mut self, e: E) -> Result<()> where E: Encodable {
-        let mut erecord = Encoded::new();
+        let mut erecord = Encoded::new().float_format(self.float_format);
+        if let Some(ref null) = self.null_value {
+            erecord = erecord.null_value(null.clone());
+        }
         try!(e.encode(&mut erecord));
         self.write(erecord.unwrap().into_iter())
     }
@@ -260,6 +270,12 @@ impl<W: io::Write> Writer<W> {
     #[doc(hidden)]
     pub fn write_iter<'a, I, F>(&mut self, r: I) -> Result<()>
             where I: Iterator<Item=Result<F>>, F: BorrowBytes {
+        if let RecordTerminator::None = self.record_terminator {
+            if self.wrote_record {
+                return self.err("Cannot write more than one record \
+                                 without a record terminator.");
+            }
+        }
         let delim = self.delimiter;
         let mut count = 0;
         let mut last_len = 0;
@@ -281,9 +297,36 @@ impl<W: io::Write> Writer<W> {
             try!(self.w_bytes(&[q, q]));
         }
         try!(self.w_lineterm());
+        self.wrote_record = true;
         self.set_first_len(count)
     }
 
+    /// Writes raw bytes verbatim.
+    ///
+    /// No quoting or escaping is performed and no record terminator is
+    /// added, so `raw` should normally contain one or more complete records,
+    /// including their terminators. Raw records are also not checked against
+    /// the length of other records.
+    ///
+    /// This is useful for editing CSV data while leaving untouched records
+    /// byte-for-byte identical. The bytes of each record as they appear in
+    /// the CSV data are available from `Reader::last_raw_bytes` when
+    /// `Reader::capture_raw` is enabled. Alternatively, fields read with
+    /// `Reader::preserve_quotes` enabled keep their original quoting, so
+    /// joining them back together reproduces the original record.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut wtr = csv::Writer::from_memory();
+    /// wtr.write_raw(b"\"a\",b\r\n").unwrap();
+    /// wtr.write(vec!["c", "d"].into_iter()).unwrap();
+    /// assert_eq!(wtr.as_string(), "\"a\",b\r\nc,d\n");
+    /// ```
+    pub fn write_raw(&mut self, raw: &[u8]) -> Result<()> {
+        self.w_bytes(raw)
+    }
+
     /// Flushes the underlying buffer.
     pub fn flush(&mut self) -> Result<()> {
         self.buf.flush().map_err(From::from)
@@ -318,11 +361,32 @@ impl<W: io::Write> Writer<W> {
     ///
     /// By default, this is `RecordTerminator::Any(b'\n')`. If you want to
     /// use CRLF (`\r\n`) line endings, then use `RecordTerminator:CRLF`.
+    ///
+    /// With `RecordTerminator::None`, only a single record can be written.
     pub fn record_terminator(mut self, term: RecordTerminator) -> Writer<W> {
         self.record_terminator = term;
         self
     }
 
+    /// A convenience method for writing ASCII delimited text.
+    ///
+    /// This sets the delimiter and record terminator to the ASCII unit
+    /// separator (`\x1f`) and record separator (`\x1e`), respectively. This
+    /// mirrors `Reader::ascii`, so data written this way can be read back
+    /// with it.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut wtr = csv::Writer::from_memory().ascii();
+    /// wtr.write(vec!["a", "b,c"].into_iter()).unwrap();
+    /// assert_eq!(wtr.as_string(), "a\x1fb,c\x1e");
+    /// ```
+    pub fn ascii(self) -> Writer<W> {
+        self.delimiter(b'\x1f')
+            .record_terminator(RecordTerminator::Any(b'\x1e'))
+    }
+
     /// Set the quoting style to use when writing CSV data.
     ///
     /// By default, this is set to `QuoteStyle::Necessary`, which will only
@@ -332,6 +396,49 @@ impl<W: io::Write> Writer<W> {
         self
     }
 
+    /// Set the format of floating point numbers written by `encode`.
+    ///
+    /// By default, this is `FloatFormat::Trimmed`, which writes up to 10
+    /// decimal places. Use `FloatFormat::Fixed` to always write the same
+    /// number of decimal places (e.g., for amounts of money). Floats are
+    /// never written in scientific notation.
+    ///
+    /// This only affects `f32` and `f64` values written by `encode`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut wtr = csv::Writer::from_memory()
+    ///                           .float_format(csv::FloatFormat::Fixed(2));
+    /// wtr.encode(("a", 1u32, 10000000.0f64, 0.125f32)).unwrap();
+    /// assert_eq!(wtr.as_string(), "a,1,10000000.00,0.12\n");
+    /// ```
+    pub fn float_format(mut self, format: FloatFormat) -> Writer<W> {
+        self.float_format = format;
+        self
+    }
+
+    /// Set the field value that `encode` writes for a `None` value.
+    ///
+    /// This is the counterpart of `Reader::null_value`, so that data
+    /// written with a marker like `\N` can be read back with `None` values
+    /// intact. Without a marker, which is the default, `None` is written as
+    /// an empty field.
+    ///
+    /// This only affects values written by `encode`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// let mut wtr = csv::Writer::from_memory().null_value("\\N");
+    /// wtr.encode(("a", Some(1), None::<i32>)).unwrap();
+    /// assert_eq!(wtr.as_string(), "a,1,\\N\n");
+    /// ```
+    pub fn null_value(mut self, marker: &str) -> Writer<W> {
+        self.null_value = Some(marker.as_bytes().to_vec());
+        self
+    }
+
     /// Set the quote character to use when writing CSV data.
     ///
     /// Since the CSV parser is meant to be mostly encoding agnostic, you must
@@ -363,11 +470,33 @@ impl<W: io::Write> Writer<W> {
     /// them. e.g., `"` escapes to `""`.
     ///
     /// When disabled, quotes are escaped with the escape character (which
-    /// is `\\` by default).
+    /// is `\\` by default). The escape character is then escaped too when
+    /// it appears in a quoted field, so that the data can be read back with
+    /// `Reader::escape` set to the same character.
     pub fn double_quote(mut self, yes: bool) -> Writer<W> {
         self.double_quote = yes;
         self
     }
+
+    /// Set the capacity of the internal buffer.
+    ///
+    /// All records and fields are written to an internal buffer, which is
+    /// only written to the underlying `io::Write` when it is full or when
+    /// `flush` is called. This avoids issuing a write for every field.
+    ///
+    /// This should be called before writing any data. If data has already
+    /// been buffered, then it is flushed first. (If that flush fails, the
+    /// existing buffer is kept and the error is reported by a subsequent
+    /// write or flush.)
+    ///
+    /// The default capacity is the same as `io::BufWriter`'s default.
+    pub fn buffer_capacity(self, capacity: usize) -> Writer<W> {
+        let buf = match self.buf.into_inner() {
+            Ok(w) => io::BufWriter::with_capacity(capacity, w),
+            Err(err) => err.into_inner(),
+        };
+        Writer { buf: buf, ..self }
+    }
 }
 
 impl<W: io::Write> Writer<W> {
@@ -392,6 +521,7 @@ impl<W: io::Write> Writer<W> {
         match self.record_terminator {
             RecordTerminator::CRLF => self.w_bytes(b"\r\n"),
             RecordTerminator::Any(b) => self.w_bytes(&[b]),
+            RecordTerminator::None => Ok(()),
         }
     }
 
@@ -443,6 +573,18 @@ impl<W: io::Write> Writer<W> {
         let mut buf = Vec::with_capacity(s.len() + 2);
 
         buf.push(self.quote);
+        if !self.double_quote {
+            // The escape character must itself be escaped, or else a
+            // reader using the same escape would drop it.
+            for &b in s {
+                if b == self.quote || b == self.escape {
+                    buf.push(self.escape);
+                }
+                buf.push(b);
+            }
+            buf.push(self.quote);
+            return buf;
+        }
         loop {
             match memchr(self.quote, s) {
                 None => {
@@ -451,13 +593,8 @@ impl<W: io::Write> Writer<W> {
                 }
                 Some(next_quote) => {
                     buf.extend_from_slice(&s[..next_quote]);
-                    if self.double_quote {
-                        buf.push(self.quote);
-                        buf.push(self.quote);
-                    } else {
-                        buf.push(self.escape);
-                        buf.push(self.quote);
-                    }
+                    buf.push(self.quote);
+                    buf.push(self.quote);
                     s = &s[next_quote + 1..];
                 }
             }